@@ -0,0 +1,125 @@
+//! Prover backends shared by `mixer-cli`, `mixer-prover` and the wallet.
+
+use std::{error::Error, path::PathBuf, rc::Rc};
+
+use risc0_zkvm::{
+    Executor, ExecutorEnv, ExecutorEnvBuilder, ExternalProver, FakeReceipt, InnerReceipt, Prover, ProverOpts, Receipt,
+};
+
+/// Where receipts are made. `default_prover()` decides this from environment
+/// variables instead, so this picks the prover explicitly.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum ProverBackend {
+    /// Prove in this process; needs the `prove` feature.
+    #[default]
+    Local,
+    /// Run the guest but make a fake receipt, accepted only by dev-mode verifiers.
+    DevMode,
+    /// Prove in an `r0vm` process.
+    External,
+}
+
+/// Receipt to prove into. The program only takes the kinds its receipt policy
+/// accepts, by default succinct ones.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::ValueEnum))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "snake_case"))]
+pub enum ReceiptFormat {
+    /// A proof per segment, as large as the guest run is long.
+    Composite,
+    /// Segments compressed by recursion into a constant-size proof.
+    #[default]
+    Succinct,
+    /// Succinct proof wrapped in Groth16; needs Docker on x86 and a builtin that verifies it.
+    Groth16,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "clap", derive(clap::Args))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct ProverConfig {
+    #[cfg_attr(feature = "clap", arg(long = "prover", value_enum, env = "MIXER_PROVER", default_value_t, global = true))]
+    pub backend: ProverBackend,
+    /// `r0vm` binary of the external backend, looked up on `PATH` by default.
+    #[cfg_attr(feature = "clap", arg(long, env = "MIXER_R0VM", global = true))]
+    pub r0vm: Option<PathBuf>,
+    /// Largest segment as a power of two of cycles; lower it to prove with less memory.
+    #[cfg_attr(feature = "clap", arg(long, global = true))]
+    pub segment_limit_po2: Option<u32>,
+    /// Stop the guest after this many cycles.
+    #[cfg_attr(feature = "clap", arg(long, global = true))]
+    pub cycle_limit: Option<u64>,
+    #[cfg_attr(feature = "clap", arg(long, value_enum, default_value_t, global = true))]
+    pub receipt: ReceiptFormat,
+}
+
+impl ProverConfig {
+    /// Executor environment with the configured limits.
+    pub fn env<'a>(&self) -> ExecutorEnvBuilder<'a> {
+        let mut env = ExecutorEnv::builder();
+        if let Some(po2) = self.segment_limit_po2 {
+            env.segment_limit_po2(po2);
+        }
+        env.session_limit(self.cycle_limit);
+        env
+    }
+
+    pub fn opts(&self) -> ProverOpts {
+        match self.receipt {
+            ReceiptFormat::Composite => ProverOpts::composite(),
+            ReceiptFormat::Succinct => ProverOpts::succinct(),
+            ReceiptFormat::Groth16 => ProverOpts::groth16(),
+        }
+    }
+
+    /// Proves `elf` on `env` with the configured backend.
+    pub fn prove(&self, env: ExecutorEnv, elf: &[u8]) -> Result<Receipt, Box<dyn Error>> {
+        let prover = match self.backend {
+            ProverBackend::DevMode => return self.fake(env, elf),
+            ProverBackend::Local => self.local()?,
+            ProverBackend::External => self.external(),
+        };
+        // risc0 switches every prover to dev mode when this is set, and `r0vm` inherits it.
+        if risc0_zkvm::is_dev_mode() {
+            Err("RISC0_DEV_MODE is set, unset it or use the dev-mode backend")?;
+        }
+        Ok(prover.prove_with_opts(env, elf, &self.opts())?.receipt)
+    }
+
+    /// What risc0's dev-mode prover makes, without turning dev mode on for the
+    /// whole process through `RISC0_DEV_MODE`.
+    fn fake(&self, env: ExecutorEnv, elf: &[u8]) -> Result<Receipt, Box<dyn Error>> {
+        eprintln!("Warning: making a dev-mode receipt, which only dev-mode verifiers accept");
+        let session = self.executor().execute(env, elf)?;
+        let claim = session.receipt_claim.ok_or("The executor reported no receipt claim")?;
+        Ok(Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim)), session.journal.bytes))
+    }
+
+    fn external(&self) -> Rc<ExternalProver> {
+        Rc::new(ExternalProver::new("external", self.r0vm.clone().unwrap_or_else(|| "r0vm".into())))
+    }
+
+    #[cfg(feature = "prove")]
+    fn local(&self) -> Result<Rc<dyn Prover>, Box<dyn Error>> {
+        Ok(Rc::new(risc0_zkvm::LocalProver::new("local")))
+    }
+
+    #[cfg(not(feature = "prove"))]
+    fn local(&self) -> Result<Rc<dyn Prover>, Box<dyn Error>> {
+        Err("Built without the `prove` feature, use the external backend")?
+    }
+
+    /// Runs guests without proving, in-process when it can.
+    #[cfg(feature = "prove")]
+    pub fn executor(&self) -> Rc<dyn Executor> {
+        Rc::new(risc0_zkvm::LocalProver::new("local"))
+    }
+
+    // Executing costs no more in `r0vm` than in-process.
+    #[cfg(not(feature = "prove"))]
+    pub fn executor(&self) -> Rc<dyn Executor> {
+        self.external()
+    }
+}
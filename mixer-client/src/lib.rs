@@ -0,0 +1,250 @@
+//! Typed client for the `Mixer` program on top of `gclient`.
+//!
+//! Every handle call estimates gas, checks the signer's balance, sends the
+//! message and waits for the program's reply, which is decoded into a
+//! [`ContractHandleEvent`] and matched against what the call expects.
+
+use std::fmt;
+
+use gclient::{metadata::runtime_types::gprimitives::ActorId as ProgramId, EventProcessor, GearApi};
+use mixer_protocol::{
+    ActorId, ContractHandleAction, ContractHandleEvent, FixedTree, Frontier, MembershipTree, ReceiptPolicy, StateOutput,
+    StatePayload, WithdrawCall, DEPOSIT_AMOUNT,
+};
+use parity_scale_codec::{Decode, Encode};
+
+pub type Result<T, E = Error> = std::result::Result<T, E>;
+
+#[derive(Debug)]
+pub enum Error {
+    Gear(Box<gclient::Error>),
+    Decode(parity_scale_codec::Error),
+    /// The program failed to handle the message; holds the reply's error string.
+    Failed(String),
+    InsufficientBalance,
+    WrongDeposit,
+    UnexpectedReply(ContractHandleEvent),
+    UnexpectedState(StateOutput),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Gear(e) => write!(f, "{e}"),
+            Error::Decode(e) => write!(f, "Failed to decode reply: {e}"),
+            Error::Failed(e) => write!(f, "Message failed: {e}"),
+            Error::InsufficientBalance => write!(f, "Insufficient balance"),
+            Error::WrongDeposit => write!(f, "Deposited value does not match the number of hashes"),
+            Error::UnexpectedReply(event) => write!(f, "Unexpected reply: {event:?}"),
+            Error::UnexpectedState(output) => write!(f, "Unexpected state: {output:?}"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<gclient::Error> for Error {
+    fn from(e: gclient::Error) -> Self {
+        Error::Gear(Box::new(e))
+    }
+}
+
+impl From<parity_scale_codec::Error> for Error {
+    fn from(e: parity_scale_codec::Error) -> Self {
+        Error::Decode(e)
+    }
+}
+
+/// Value charged per unit of gas by Vara's runtime.
+pub const VALUE_PER_GAS: u128 = 6;
+
+/// Share of the estimate added to every gas limit, in percent. Withdrawals
+/// wait for the builtin's reply, and the estimate of that path runs tight.
+pub const GAS_MARGIN_PERCENT: u64 = 20;
+
+#[derive(Clone)]
+pub struct MixerClient {
+    api: GearApi,
+    program_id: [u8; 32],
+    value_per_gas: u128,
+}
+
+impl MixerClient {
+    pub fn new(api: GearApi, program_id: ActorId) -> Self {
+        Self {
+            api,
+            program_id: program_id.into(),
+            value_per_gas: VALUE_PER_GAS,
+        }
+    }
+
+    /// For runtimes that charge another [`VALUE_PER_GAS`].
+    pub fn with_value_per_gas(mut self, value_per_gas: u128) -> Self {
+        self.value_per_gas = value_per_gas;
+        self
+    }
+
+    pub fn api(&self) -> &GearApi {
+        &self.api
+    }
+
+    pub fn program_id(&self) -> ActorId {
+        self.program_id.into()
+    }
+
+    /// Deposits one note per hash, attaching `DEPOSIT_AMOUNT` for each.
+    pub async fn deposit(&self, hashes: Vec<[u8; 32]>) -> Result<()> {
+        let value = hashes.len() as u128 * DEPOSIT_AMOUNT;
+        match self.send(ContractHandleAction::Deposit { hashes }, value).await?.0 {
+            ContractHandleEvent::Deposited => Ok(()),
+            ContractHandleEvent::WrongDeposit => Err(Error::WrongDeposit),
+            event => Err(Error::UnexpectedReply(event)),
+        }
+    }
+
+    pub async fn enqueue(&self, hashes: Vec<[u8; 32]>) -> Result<()> {
+        let value = hashes.len() as u128 * DEPOSIT_AMOUNT;
+        match self.send(ContractHandleAction::Enqueue { hashes }, value).await?.0 {
+            ContractHandleEvent::Enqueued => Ok(()),
+            ContractHandleEvent::WrongDeposit => Err(Error::WrongDeposit),
+            event => Err(Error::UnexpectedReply(event)),
+        }
+    }
+
+    pub async fn insert_batch(&self, image_id_receipt: Vec<u8>) -> Result<()> {
+        match self.send(ContractHandleAction::InsertBatch { image_id_receipt }, 0).await?.0 {
+            ContractHandleEvent::BatchInserted => Ok(()),
+            event => Err(Error::UnexpectedReply(event)),
+        }
+    }
+
+    /// Returns the value sent back with the reply. Split withdrawals pay the
+    /// committed recipients directly, so they return zero.
+    pub async fn withdraw(&self, image_id_receipt: Vec<u8>) -> Result<u128> {
+        match self.send(ContractHandleAction::Withdraw { image_id_receipt }, 0).await? {
+            (ContractHandleEvent::Withdrawed | ContractHandleEvent::WithdrawnSplit, value) => Ok(value),
+            (event, _) => Err(Error::UnexpectedReply(event)),
+        }
+    }
+
+    /// Returns whether the call succeeded; on failure the value went to `call.fallback`.
+    pub async fn withdraw_and_call(&self, image_id_receipt: Vec<u8>, call: WithdrawCall) -> Result<bool> {
+        let action = ContractHandleAction::WithdrawAndCall { image_id_receipt, call };
+        match self.send(action, 0).await?.0 {
+            ContractHandleEvent::WithdrawnAndCalled { success } => Ok(success),
+            event => Err(Error::UnexpectedReply(event)),
+        }
+    }
+
+    /// Admin only: limits the receipts `withdraw` and `insert_batch` accept.
+    pub async fn set_receipt_policy(&self, policy: ReceiptPolicy) -> Result<()> {
+        match self.send(ContractHandleAction::SetReceiptPolicy { policy }, 0).await?.0 {
+            ContractHandleEvent::ReceiptPolicySet => Ok(()),
+            event => Err(Error::UnexpectedReply(event)),
+        }
+    }
+
+    /// Sends `action` with `value` attached and returns the decoded reply
+    /// together with the value it carried.
+    pub async fn send(&self, action: ContractHandleAction, value: u128) -> Result<(ContractHandleEvent, u128)> {
+        let program_id = ProgramId(self.program_id);
+        let payload = action.encode();
+
+        let gas_info = self
+            .api
+            .calculate_handle_gas(None, program_id.into(), payload.clone(), value, true)
+            .await?;
+        let gas_limit = gas_info.min_limit.saturating_mul(100 + GAS_MARGIN_PERCENT) / 100;
+        // The signer pays for the whole limit up front and gets the rest back.
+        let cost = (gas_limit as u128).saturating_mul(self.value_per_gas).saturating_add(value);
+        let balance = self.api.free_balance(self.api.account_id()).await?;
+        if cost > balance {
+            return Err(Error::InsufficientBalance);
+        }
+
+        let mut listener = self.api.subscribe().await?;
+        let (message_id, _) = self.api.send_message_bytes(program_id.into(), payload, gas_limit, value).await?;
+        let (_, reply, value) = listener.reply_bytes_on(message_id).await?;
+        let reply = reply.map_err(Error::Failed)?;
+
+        Ok((ContractHandleEvent::decode(&mut &reply[..])?, value))
+    }
+
+    pub async fn root(&self) -> Result<[u8; 32]> {
+        match self.state(StatePayload::Root).await? {
+            StateOutput::Root { res } => Ok(res),
+            output => Err(Error::UnexpectedState(output)),
+        }
+    }
+
+    pub async fn leaves(&self) -> Result<Vec<[u8; 32]>> {
+        match self.state(StatePayload::Leaves).await? {
+            StateOutput::Leaves { res } => Ok(res),
+            output => Err(Error::UnexpectedState(output)),
+        }
+    }
+
+    pub async fn leaves_len(&self) -> Result<u64> {
+        match self.state(StatePayload::LeavesLen).await? {
+            StateOutput::LeavesLen { res } => Ok(res),
+            output => Err(Error::UnexpectedState(output)),
+        }
+    }
+
+    /// At most `limit` leaves starting at index `from`.
+    pub async fn leaves_page(&self, from: u64, limit: u32) -> Result<Vec<[u8; 32]>> {
+        match self.state(StatePayload::LeavesPage { from, limit }).await? {
+            StateOutput::LeavesPage { res } => Ok(res),
+            output => Err(Error::UnexpectedState(output)),
+        }
+    }
+
+    pub async fn is_spent(&self, nullifiers: Vec<[u8; 32]>) -> Result<Vec<bool>> {
+        let len = nullifiers.len();
+        match self.state(StatePayload::IsSpent { nullifiers }).await? {
+            StateOutput::IsSpent { res } => {
+                Ok((0..len).map(|i| res.get(i / 8).is_some_and(|byte| byte & (1 << (i % 8)) != 0)).collect())
+            },
+            output => Err(Error::UnexpectedState(output)),
+        }
+    }
+
+    pub async fn leaf_index_of(&self, commitments: Vec<[u8; 32]>) -> Result<Vec<Option<u64>>> {
+        match self.state(StatePayload::LeafIndexOf { commitments }).await? {
+            StateOutput::LeafIndexOf { res } => Ok(res),
+            output => Err(Error::UnexpectedState(output)),
+        }
+    }
+
+    pub async fn queue(&self) -> Result<Vec<[u8; 32]>> {
+        match self.state(StatePayload::Queue).await? {
+            StateOutput::Queue { res } => Ok(res),
+            output => Err(Error::UnexpectedState(output)),
+        }
+    }
+
+    pub async fn frontier(&self) -> Result<Frontier> {
+        match self.state(StatePayload::Frontier).await? {
+            StateOutput::Frontier { peaks, leaves_len } => Ok(Frontier { peaks, len: leaves_len }),
+            output => Err(Error::UnexpectedState(output)),
+        }
+    }
+
+    pub async fn fixed_tree(&self) -> Result<FixedTree> {
+        match self.state(StatePayload::FixedTree).await? {
+            StateOutput::FixedTree { filled, root, leaves_len } => Ok(FixedTree { filled, root, len: leaves_len }),
+            output => Err(Error::UnexpectedState(output)),
+        }
+    }
+
+    pub async fn is_known_root(&self, root: [u8; 32], tree: MembershipTree) -> Result<bool> {
+        match self.state(StatePayload::KnownRoot { root, tree }).await? {
+            StateOutput::KnownRoot { res } => Ok(res),
+            output => Err(Error::UnexpectedState(output)),
+        }
+    }
+
+    pub async fn state(&self, payload: StatePayload) -> Result<StateOutput> {
+        Ok(self.api.read_state(ProgramId(self.program_id).into(), payload.encode()).await?)
+    }
+}
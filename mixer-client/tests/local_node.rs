@@ -0,0 +1,59 @@
+//! Runs against a local development node with the `Mixer` program built:
+//!
+//! ```sh
+//! gear --dev --tmp &
+//! (cd mixer-contract && cargo build --release)
+//! (cd mixer-client && cargo test -- --ignored)
+//! ```
+//!
+//! `MIXER_WASM` points at another build of the program.
+
+use std::{error::Error, path::PathBuf};
+
+use gclient::{EventProcessor, GearApi};
+use mixer_client::MixerClient;
+use mixer_protocol::{ActorId, DEPOSIT_AMOUNT};
+
+fn wasm() -> PathBuf {
+    std::env::var_os("MIXER_WASM").map(PathBuf::from).unwrap_or_else(|| {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+            .join("../mixer-contract/target/wasm32-unknown-unknown/release/mixer_contract.opt.wasm")
+    })
+}
+
+/// A fresh `Mixer` program uploaded by Alice.
+async fn upload(api: &GearApi) -> Result<MixerClient, Box<dyn Error>> {
+    let code = std::fs::read(wasm())?;
+    let gas = api.calculate_upload_gas(None, code, vec![], 0, true).await?.min_limit;
+
+    let mut listener = api.subscribe().await?;
+    let salt = gclient::now_micros().to_le_bytes();
+    let (message_id, program_id, _) = api.upload_program_bytes_by_path(wasm(), salt, [], gas, 0).await?;
+    assert!(listener.message_processed(message_id).await?.succeed());
+
+    Ok(MixerClient::new(api.clone(), ActorId::new(program_id.into_bytes())))
+}
+
+#[tokio::test]
+#[ignore = "needs a local node"]
+async fn deposit_and_read_state() -> Result<(), Box<dyn Error>> {
+    let api = GearApi::dev().await?;
+    let client = upload(&api).await?;
+    let empty = client.root().await?;
+    assert_eq!(client.leaves_len().await?, 0);
+
+    let hashes = vec![[1; 32], [2; 32]];
+    let before = api.free_balance(api.account_id()).await?;
+    client.deposit(hashes.clone()).await?;
+    assert!(before - api.free_balance(api.account_id()).await? >= 2 * DEPOSIT_AMOUNT);
+
+    assert_eq!(client.leaves_len().await?, 2);
+    assert_eq!(client.leaves().await?.len(), 2);
+    assert_eq!(client.leaves_page(1, 10).await?.len(), 1);
+    assert_ne!(client.root().await?, empty);
+    assert_eq!(client.frontier().await?.len, 2);
+    assert_eq!(client.fixed_tree().await?.len, 2);
+    assert_eq!(client.is_spent(vec![[0; 32]]).await?, [false]);
+    assert!(client.queue().await?.is_empty());
+    Ok(())
+}
@@ -0,0 +1,5 @@
+// Generated by `risc0-prover/methods/build.rs` with `MIXER_EXPORT_GUESTS=1`, do not edit.
+// Built with a local toolchain, which other toolchains don't reproduce.
+pub const GUEST_ID: [u32; 8] = [2534542711, 3561023327, 2271951369, 1724448245, 4245070616, 650593024, 2357635195, 329647735];
+pub const BATCH_GUEST_ID: [u32; 8] = [1483454909, 608503015, 3504208103, 2736576881, 1125742255, 928284870, 1496137705, 2818865432];
+pub const AGGREGATE_GUEST_ID: [u32; 8] = [1651028333, 3386112538, 3033057210, 2512904568, 3777622400, 2433421543, 2653584145, 593716659];
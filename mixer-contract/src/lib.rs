@@ -2,85 +2,76 @@
 
 use gstd::{
     collections::{HashMap, HashSet},
-    exec, msg, ActorId, Vec,
+    exec, msg, vec, ActorId, Vec,
 };
-use io::{ContractHandleAction, ContractHandleEvent, StateOutput, StatePayload, TransactionInfo};
-use risc0_zkvm::{guest::sha::Impl, sha::Sha256};
-use rs_merkle::MerkleTree;
+use io::{
+    zero_hashes, AggregateOutputs, BatchOutputs, CallRequest, ContractHandleAction, ContractHandleEvent,
+    FixedTree, Frontier, MembershipTree, PublicOutputs, ReceiptKind, ReceiptPolicy, StateChunk, StateOutput,
+    StatePayload, StateSection, TransactionInfo, WithdrawCall, WithdrawOutput, DEPOSIT_AMOUNT,
+};
+use risc0_zkvm::sha::{Impl, Sha256};
 
 const RISC0_BUILTIN: ActorId = ActorId::new(hex_literal::hex!(
     "1ef25efb2be22235d221e0570bf57efd2b5483a39088cff6e9144b1125696632"
 ));
 
-const GUEST_ID: [u32; 8] = [3059853664, 1138407129, 918623963, 946626020, 282452322, 3875698598, 154530926, 35396726];
-const VARA_UNIT: u128 = 1_000_000_000_000;
-const VARA_DEPOSIT_AMOUNT: u32 = 10;
-const DEPOSIT_AMOUNT: u128 = VARA_UNIT * VARA_DEPOSIT_AMOUNT as u128;
+pub mod guest_ids;
 
-#[derive(Clone)]
-pub struct DigestWrapper(Impl);
+// Aggregate journals name the per-note image id, checked against `GUEST_ID`.
+use guest_ids::{AGGREGATE_GUEST_ID, BATCH_GUEST_ID, GUEST_ID};
 
-impl rs_merkle::Hasher for DigestWrapper {
-    type Hash = [u8; 32];
-    fn hash(data: &[u8]) -> Self::Hash {
-        (*Impl::hash_bytes(data)).into()
-    }
-}
+pub use io::MerkleHasher;
 
-pub struct PublicOutputs {
-    pub root: [u8; 32],
-    pub used: Vec<[u8; 32]>,
-}
-
-fn deserialize_public_outputs(bytes: Vec<u8>) -> PublicOutputs {
-    let bytes_len = bytes.len();
-    assert!(bytes_len >= 132, "Wrong public outputs {:?}", bytes);
-    let mut root = [0u8; 32];
-
-    let mut chunks = bytes.chunks_exact(4);
-    for i in 0..32 {
-        root[i] = chunks.next().unwrap()[0];
-    }
-    let len = u32::from_le_bytes(chunks.next().unwrap().try_into().unwrap()) as usize;
-    assert_eq!(
-        bytes_len,
-        132 + len * 32 * 4,
-        "Wrong public outputs {:?}",
-        bytes
-    );
-
-    let mut used = Vec::with_capacity(len);
-    for _ in 0..len {
-        let mut elem = [0u8; 32];
-        for i in 0..32 {
-            elem[i] = chunks.next().unwrap()[0];
-        }
-        used.push(elem);
-    }
-    PublicOutputs { root, used }
+fn hash_call(call: &WithdrawCall) -> [u8; 32] {
+    let preimage = CallRequest::from(call.clone()).preimage();
+    (*Impl::hash_bytes(&preimage)).into()
 }
 
 #[derive(Default)]
 pub struct Mixer {
-    pub merkle_tree: MerkleTree<DigestWrapper>,
+    /// Every inserted leaf, batched or not. Wallets page through them to
+    /// build membership proofs and migration exports them, so batches move
+    /// the tree hashing into a proof but keep the leaves on-chain.
+    pub leaves: Vec<[u8; 32]>,
+    pub frontier: Frontier,
+    pub roots: Vec<[u8; 32]>,
+    /// Leaf count of the append-only tree each of `roots` was recorded at.
+    pub root_leaves: HashMap<[u8; 32], u64>,
+    pub fixed_tree: FixedTree,
+    /// [`zero_hashes`] for [`MerkleHasher`], computed once at init.
+    pub zeros: Vec<[u8; 32]>,
+    pub fixed_roots: Vec<[u8; 32]>,
+    pub queue: Vec<[u8; 32]>,
     pub withdrawn: HashSet<[u8; 32]>,
     pub withdrawn_vec: Vec<[u8; 32]>,
+    pub leaf_indices: HashMap<[u8; 32], u64>,
     pub history: HashMap<ActorId, Vec<TransactionInfo>>,
+    pub admin: ActorId,
+    pub frozen: bool,
+    pub importing_from: Option<ActorId>,
+    pub receipt_policy: ReceiptPolicy,
 }
 
 impl Mixer {
-    fn deposit(&mut self, mut hashes: Vec<[u8; 32]>) {
+    fn deposit(&mut self, hashes: Vec<[u8; 32]>) {
+        self.assert_active();
         let hash_len = hashes.len();
-        if hash_len as u128 != msg::value() / DEPOSIT_AMOUNT {
+        if hash_len == 0 || hash_len as u128 != msg::value() / DEPOSIT_AMOUNT {
             msg::reply(ContractHandleEvent::WrongDeposit, msg::value())
                 .expect("Error in wrong deposit reply");
             return;
         }
-        self.merkle_tree.append(&mut hashes).commit();
+        for hash in hashes {
+            self.push_leaf(hash);
+            self.frontier.append::<MerkleHasher>(hash);
+            self.fixed_tree.append::<MerkleHasher>(&self.zeros, hash);
+        }
+        self.push_root(self.frontier.root::<MerkleHasher>());
+        self.fixed_roots.push(self.fixed_tree.root(&self.zeros));
 
         self.history
             .entry(msg::source())
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(TransactionInfo {
                 amount: hash_len as i32,
                 time: exec::block_timestamp(),
@@ -89,45 +80,369 @@ impl Mixer {
         msg::reply(ContractHandleEvent::Deposited, 0).expect("Error in reply in deposit");
     }
 
+    fn enqueue(&mut self, mut hashes: Vec<[u8; 32]>) {
+        self.assert_active();
+        let hash_len = hashes.len();
+        if hash_len == 0 || hash_len as u128 != msg::value() / DEPOSIT_AMOUNT {
+            msg::reply(ContractHandleEvent::WrongDeposit, msg::value())
+                .expect("Error in wrong deposit reply");
+            return;
+        }
+        self.queue.append(&mut hashes);
+
+        self.history
+            .entry(msg::source())
+            .or_default()
+            .push(TransactionInfo {
+                amount: hash_len as i32,
+                time: exec::block_timestamp(),
+            });
+
+        msg::reply(ContractHandleEvent::Enqueued, 0).expect("Error in reply in enqueue");
+    }
+
+    async fn insert_batch(&mut self, image_id_receipt: Vec<u8>) {
+        self.assert_active();
+        self.check_receipt(&image_id_receipt);
+        let image_id: [u32; 8] = postcard::from_bytes(&image_id_receipt).expect("Wrong image id");
+        assert_eq!(image_id, BATCH_GUEST_ID, "Wrong image id in batch proof");
+
+        let batch_outputs = msg::send_bytes_for_reply(RISC0_BUILTIN, image_id_receipt, 0, 0)
+            .expect("Error in send_bytes_for_reply in insert_batch")
+            .await
+            .expect("Error in your zk-proof");
+
+        let BatchOutputs {
+            old_root,
+            old_len,
+            count,
+            batch_hash,
+            new_root,
+            new_peaks,
+            old_fixed,
+            new_fixed_root,
+            new_filled,
+        } = BatchOutputs::from_journal(&batch_outputs).expect("Wrong batch outputs");
+        self.assert_active();
+
+        assert_eq!(old_len, self.frontier.len, "Batch was proven against another tree");
+        assert_eq!(
+            old_root,
+            self.roots.last().copied().unwrap_or_default(),
+            "Batch was proven against another root"
+        );
+        assert_eq!(
+            old_fixed,
+            self.fixed_tree.commitment::<MerkleHasher>(),
+            "Batch was proven against another fixed tree"
+        );
+        assert!(
+            count != 0 && count as usize <= self.queue.len(),
+            "Wrong batch size"
+        );
+
+        let batch: Vec<[u8; 32]> = self.queue.drain(..count as usize).collect();
+        assert_eq!(
+            <[u8; 32]>::from(*Impl::hash_bytes(&batch.concat())),
+            batch_hash,
+            "Batch doesn't match the queue"
+        );
+
+        // Wallets still need the leaves, only their hashing was proven.
+        for hash in batch {
+            self.push_leaf(hash);
+        }
+        self.frontier = Frontier {
+            peaks: new_peaks,
+            len: old_len + count,
+        };
+        self.fixed_tree = FixedTree {
+            filled: new_filled,
+            root: new_fixed_root,
+            len: self.fixed_tree.len + count,
+        };
+        self.push_root(new_root);
+        self.fixed_roots.push(new_fixed_root);
+
+        msg::reply(ContractHandleEvent::BatchInserted, 0).expect("Error in reply in insert_batch");
+    }
+
+    fn push_root(&mut self, root: [u8; 32]) {
+        self.roots.push(root);
+        self.root_leaves.insert(root, self.frontier.len);
+    }
+
+    fn push_leaf(&mut self, hash: [u8; 32]) {
+        self.leaf_indices
+            .entry(hash)
+            .or_insert(self.leaves.len() as u64);
+        self.leaves.push(hash);
+    }
+
     async fn withdraw(&mut self, image_id_receipt: Vec<u8>) {
+        let PublicOutputs {
+            used,
+            call,
+            outputs,
+            ..
+        } = self.verify_withdrawal(image_id_receipt).await;
+        assert!(call.is_none(), "Proof is bound to a call, use WithdrawAndCall");
+
+        if outputs.is_empty() {
+            let amount = self.spend(used);
+
+            msg::reply(ContractHandleEvent::Withdrawed, amount * DEPOSIT_AMOUNT)
+                .expect("Error in reply in withdraw");
+            return;
+        }
+
+        self.spend(used);
+        for WithdrawOutput {
+            recipient,
+            note_count,
+        } in outputs
+        {
+            msg::send_bytes(recipient.into(), [], note_count as u128 * DEPOSIT_AMOUNT)
+                .expect("Error in payout in withdraw");
+        }
+
+        msg::reply(ContractHandleEvent::WithdrawnSplit, 0).expect("Error in reply in withdraw");
+    }
+
+    async fn withdraw_and_call(&mut self, image_id_receipt: Vec<u8>, call: WithdrawCall) {
+        let PublicOutputs {
+            used,
+            call: call_hash,
+            outputs,
+            ..
+        } = self.verify_withdrawal(image_id_receipt).await;
+        assert_eq!(call_hash, Some(hash_call(&call)), "Proof is bound to another call");
+        assert!(outputs.is_empty(), "Proof is bound to split outputs");
+
+        let value = self.spend(used) * DEPOSIT_AMOUNT;
+        let WithdrawCall {
+            destination,
+            fallback,
+            payload,
+        } = call;
+
+        let success = msg::send_bytes_for_reply(destination, payload, value, 0)
+            .expect("Error in send_bytes_for_reply in withdraw_and_call")
+            .await
+            .is_ok();
+        if !success {
+            msg::send_bytes(fallback, [], value).expect("Error in refund to fallback");
+        }
+
+        msg::reply(ContractHandleEvent::WithdrawnAndCalled { success }, 0)
+            .expect("Error in reply in withdraw_and_call");
+    }
+
+    async fn verify_withdrawal(&mut self, image_id_receipt: Vec<u8>) -> PublicOutputs {
+        self.assert_active();
+        self.check_receipt(&image_id_receipt);
         let image_id: [u32; 8] = postcard::from_bytes(&image_id_receipt).expect("Wrong image id");
-        assert_eq!(image_id, GUEST_ID, "Wrong image id in proof");
+        assert!(
+            image_id == GUEST_ID || image_id == AGGREGATE_GUEST_ID,
+            "Wrong image id in proof"
+        );
 
-        let public_outputs = msg::send_bytes_for_reply(RISC0_BUILTIN, image_id_receipt, 0, 0)
+        let journal = msg::send_bytes_for_reply(RISC0_BUILTIN, image_id_receipt, 0, 0)
             .expect("Error in send_bytes_for_reply in winthdraw")
             .await
             .expect("Error in your zk-proof");
 
-        let PublicOutputs { root, used } = deserialize_public_outputs(public_outputs);
+        let public_outputs = if image_id == GUEST_ID {
+            PublicOutputs::from_journal(&journal).expect("Wrong public outputs")
+        } else {
+            let aggregate = AggregateOutputs::from_journal(&journal).expect("Wrong aggregate outputs");
+            assert_eq!(aggregate.note_image_id, GUEST_ID, "Wrong note image id in aggregate proof");
+            aggregate.withdraw
+        };
+        self.assert_active();
 
         assert!(
-            self.merkle_tree
-                .history
-                .iter()
-                .rfind(|&&x| x == root)
-                .is_some(),
+            self.is_known_root(public_outputs.root, public_outputs.tree),
             "There has never been such a root"
         );
 
-        let mut amount = 0;
+        public_outputs
+    }
+
+    /// Whether `root` was recorded for `tree`, including the leaf count of
+    /// the append-only tree, so a proof can't pick the size it is checked at.
+    fn is_known_root(&self, root: [u8; 32], tree: MembershipTree) -> bool {
+        match tree {
+            MembershipTree::AppendOnly { leaves } => self.root_leaves.get(&root) == Some(&leaves),
+            MembershipTree::Fixed => self.fixed_roots.iter().rfind(|&&x| x == root).is_some(),
+        }
+    }
+
+    /// Marks `used` spent before anything is paid or called, so a replayed
+    /// receipt, or a new one for a spent note, fails as a whole.
+    fn spend(&mut self, used: Vec<[u8; 32]>) -> u128 {
+        let amount = used.len() as u128;
         for u in used {
-            if let gstd::collections::hash_set::Entry::Vacant(e) = self.withdrawn.entry(u) {
-                amount += 1;
-                e.insert();
-                self.withdrawn_vec.push(u);
-            }
+            assert!(self.withdrawn.insert(u), "Note is already spent");
+            self.withdrawn_vec.push(u);
         }
 
         self.history
             .entry(msg::source())
-            .or_insert_with(Vec::new)
+            .or_default()
             .push(TransactionInfo {
                 amount: -(amount as i32),
                 time: exec::block_timestamp(),
             });
 
-        msg::reply(ContractHandleEvent::Withdrawed, amount * DEPOSIT_AMOUNT)
-            .expect("Error in reply in withdraw");
+        amount
+    }
+
+    /// Rejects receipts the policy rules out before paying for the builtin call.
+    fn check_receipt(&self, image_id_receipt: &[u8]) {
+        assert!(
+            image_id_receipt.len() <= self.receipt_policy.max_receipt_bytes as usize,
+            "Receipt is too large"
+        );
+        let kind = ReceiptKind::of(image_id_receipt).expect("Unknown receipt kind");
+        assert!(
+            self.receipt_policy.accepted_kinds.contains(&kind),
+            "Receipt kind is not accepted"
+        );
+    }
+
+    fn set_receipt_policy(&mut self, policy: ReceiptPolicy) {
+        assert_eq!(msg::source(), self.admin, "Only admin can set the receipt policy");
+        self.receipt_policy = policy;
+
+        msg::reply(ContractHandleEvent::ReceiptPolicySet, 0)
+            .expect("Error in reply in set_receipt_policy");
+    }
+
+    fn assert_active(&self) {
+        assert!(!self.frozen, "Mixer is frozen for migration");
+        assert!(self.importing_from.is_none(), "Mixer is importing state");
+    }
+
+    fn assert_admin(&self) {
+        assert_eq!(msg::source(), self.admin, "Only admin can migrate state");
+    }
+
+    fn freeze(&mut self) {
+        self.assert_admin();
+        self.assert_active();
+        assert!(self.queue.is_empty(), "Queued deposits must be inserted before freezing");
+        self.frozen = true;
+
+        msg::reply(ContractHandleEvent::Frozen, 0).expect("Error in reply in freeze");
+    }
+
+    fn export_state(&self, section: StateSection, from: u64, limit: u32) {
+        self.assert_admin();
+        // Anything that lands after a page was exported would be left behind.
+        assert!(self.frozen, "Mixer must be frozen before its state is exported");
+        let source = match section {
+            StateSection::Leaves => &self.leaves,
+            StateSection::Roots => &self.roots,
+            StateSection::Nullifiers => &self.withdrawn_vec,
+            StateSection::FixedRoots => &self.fixed_roots,
+        };
+        let total = source.len() as u64;
+        let page = source.iter().skip(from as usize).take(limit as usize).copied();
+        let chunk = match section {
+            StateSection::Leaves => StateChunk::Leaves(page.collect()),
+            StateSection::Roots => StateChunk::Roots(page.map(|root| (root, self.root_leaves[&root])).collect()),
+            StateSection::Nullifiers => StateChunk::Nullifiers(page.collect()),
+            StateSection::FixedRoots => StateChunk::FixedRoots(page.collect()),
+        };
+
+        msg::reply(ContractHandleEvent::StateExported { chunk, total }, 0)
+            .expect("Error in reply in export_state");
+    }
+
+    fn begin_import(&mut self, predecessor: ActorId) {
+        self.assert_admin();
+        assert!(
+            self.leaves.is_empty()
+                && self.roots.is_empty()
+                && self.queue.is_empty()
+                && self.withdrawn.is_empty(),
+            "State can be imported only into a fresh mixer"
+        );
+        self.importing_from = Some(predecessor);
+
+        msg::reply(ContractHandleEvent::ImportStarted, 0).expect("Error in reply in begin_import");
+    }
+
+    fn import_state(&mut self, chunk: StateChunk) {
+        self.assert_admin();
+        assert!(self.importing_from.is_some(), "Import hasn't been started");
+        match chunk {
+            StateChunk::Leaves(leaves) => {
+                for hash in leaves {
+                    self.push_leaf(hash);
+                    self.frontier.append::<MerkleHasher>(hash);
+                    self.fixed_tree.append::<MerkleHasher>(&self.zeros, hash);
+                }
+            }
+            StateChunk::Roots(roots) => {
+                for (root, leaves) in roots {
+                    self.roots.push(root);
+                    self.root_leaves.insert(root, leaves);
+                }
+            }
+            StateChunk::Nullifiers(nullifiers) => {
+                for u in nullifiers {
+                    if self.withdrawn.insert(u) {
+                        self.withdrawn_vec.push(u);
+                    }
+                }
+            }
+            StateChunk::FixedRoots(mut roots) => self.fixed_roots.append(&mut roots),
+        }
+
+        msg::reply(ContractHandleEvent::StateImported, 0).expect("Error in reply in import_state");
+    }
+
+    fn handover(&mut self, successor: ActorId) {
+        self.assert_admin();
+        assert!(self.frozen, "Mixer must be frozen before handover");
+
+        let action = ContractHandleAction::AcceptHandover {
+            leaves: self.leaves.len() as u64,
+            nullifiers: self.withdrawn_vec.len() as u64,
+        };
+        msg::send(successor, action, exec::value_available()).expect("Error in sending handover");
+        msg::reply(ContractHandleEvent::HandedOver, 0).expect("Error in reply in handover");
+    }
+
+    fn accept_handover(&mut self, leaves: u64, nullifiers: u64) {
+        assert_eq!(
+            Some(msg::source()),
+            self.importing_from,
+            "Handover is accepted only from the predecessor"
+        );
+        assert_eq!(self.leaves.len() as u64, leaves, "Not every leaf was imported");
+        assert_eq!(self.withdrawn_vec.len() as u64, nullifiers, "Not every nullifier was imported");
+        assert!(
+            msg::value() >= leaves.saturating_sub(nullifiers) as u128 * DEPOSIT_AMOUNT,
+            "Handover doesn't carry the value of unspent notes"
+        );
+        assert_eq!(
+            self.frontier.root::<MerkleHasher>(),
+            self.roots.last().copied().unwrap_or_default(),
+            "Imported leaves don't match imported roots"
+        );
+        assert!(
+            self.fixed_roots
+                .last()
+                .map_or(self.leaves.is_empty(), |&root| root == self.fixed_tree.root(&self.zeros)),
+            "Imported leaves don't match imported fixed roots"
+        );
+        self.importing_from = None;
+
+        msg::reply(ContractHandleEvent::HandoverAccepted, 0)
+            .expect("Error in reply in accept_handover");
     }
 }
 
@@ -136,6 +451,8 @@ static mut MIXER: Option<Mixer> = None;
 #[no_mangle]
 unsafe extern "C" fn init() {
     MIXER = Some(Mixer {
+        admin: msg::source(),
+        zeros: zero_hashes::<MerkleHasher>(),
         ..Default::default()
     });
 }
@@ -150,6 +467,25 @@ async fn main() {
         ContractHandleAction::Withdraw { image_id_receipt } => {
             mixer.withdraw(image_id_receipt).await
         }
+        ContractHandleAction::Enqueue { hashes } => mixer.enqueue(hashes),
+        ContractHandleAction::InsertBatch { image_id_receipt } => {
+            mixer.insert_batch(image_id_receipt).await
+        }
+        ContractHandleAction::ExportState {
+            section,
+            from,
+            limit,
+        } => mixer.export_state(section, from, limit),
+        ContractHandleAction::BeginImport { predecessor } => mixer.begin_import(predecessor),
+        ContractHandleAction::ImportState { chunk } => mixer.import_state(chunk),
+        ContractHandleAction::Handover { successor } => mixer.handover(successor),
+        ContractHandleAction::AcceptHandover { leaves, nullifiers } => mixer.accept_handover(leaves, nullifiers),
+        ContractHandleAction::WithdrawAndCall {
+            image_id_receipt,
+            call,
+        } => mixer.withdraw_and_call(image_id_receipt, call).await,
+        ContractHandleAction::SetReceiptPolicy { policy } => mixer.set_receipt_policy(policy),
+        ContractHandleAction::Freeze => mixer.freeze(),
     };
 }
 
@@ -159,15 +495,15 @@ extern "C" fn state() {
     let mixer = unsafe { MIXER.take().unwrap_or_default() };
     match payload {
         StatePayload::Root => {
-            let res = mixer.merkle_tree.root().unwrap_or_default();
+            let res = mixer.roots.last().copied().unwrap_or_default();
             msg::reply(StateOutput::Root {res}, 0).expect("Failed to share state");
         }
         StatePayload::Leaves => {
-            let res = mixer.merkle_tree.leaves().unwrap_or_default();
+            let res = mixer.leaves;
             msg::reply(StateOutput::Leaves { res }, 0).expect("Failed to share state");
         }
         StatePayload::LeavesLen => {
-            let res = mixer.merkle_tree.leaves().unwrap_or_default().len() as u64;
+            let res = mixer.leaves.len() as u64;
             msg::reply(StateOutput::LeavesLen { res }, 0).expect("Failed to share state");
         }
         StatePayload::Withdrawn { from } => {
@@ -183,7 +519,7 @@ extern "C" fn state() {
             .history
             .get(&user)
             .map(|history| history.get(from as usize..).unwrap_or_default().to_vec())
-            .filter(|history_from| history_from.len() != 0)
+            .filter(|history_from| !history_from.is_empty())
             .unwrap_or_default();
             msg::reply(StateOutput::HistoryOneFrom { res }, 0).expect("Failed to share state");
         }
@@ -195,7 +531,7 @@ extern "C" fn state() {
                         .history
                         .get(&user)
                         .map(|history| (user, history.get(from as usize..).unwrap_or_default().to_vec()))
-                        .filter(|(_, history_from)| history_from.len() != 0)
+                        .filter(|(_, history_from)| !history_from.is_empty())
                 })
                 .collect();
             msg::reply(StateOutput::HistoryFrom { res }, 0).expect("Failed to share state");
@@ -204,5 +540,58 @@ extern "C" fn state() {
             let res = mixer.history.into_iter().collect();
             msg::reply(StateOutput::HistoryAll { res }, 0).expect("Failed to share state");
         }
+        StatePayload::IsSpent { nullifiers } => {
+            let mut res = vec![0u8; nullifiers.len().div_ceil(8)];
+            for (i, nullifier) in nullifiers.iter().enumerate() {
+                if mixer.withdrawn.contains(nullifier) {
+                    res[i / 8] |= 1 << (i % 8);
+                }
+            }
+            msg::reply(StateOutput::IsSpent { res }, 0).expect("Failed to share state");
+        }
+        StatePayload::LeafIndexOf { commitments } => {
+            let res = commitments
+                .iter()
+                .map(|commitment| mixer.leaf_indices.get(commitment).copied())
+                .collect();
+            msg::reply(StateOutput::LeafIndexOf { res }, 0).expect("Failed to share state");
+        }
+        StatePayload::Queue => {
+            let res = mixer.queue;
+            msg::reply(StateOutput::Queue { res }, 0).expect("Failed to share state");
+        }
+        StatePayload::Frontier => {
+            let Frontier { peaks, len } = mixer.frontier;
+            msg::reply(StateOutput::Frontier { peaks, leaves_len: len }, 0)
+                .expect("Failed to share state");
+        }
+        StatePayload::Migration => {
+            let res = StateOutput::Migration {
+                admin: mixer.admin,
+                frozen: mixer.frozen,
+                importing_from: mixer.importing_from,
+            };
+            msg::reply(res, 0).expect("Failed to share state");
+        }
+        StatePayload::LeavesPage { from, limit } => {
+            let res = mixer
+                .leaves
+                .get(from as usize..)
+                .unwrap_or_default()
+                .iter()
+                .take(limit as usize)
+                .copied()
+                .collect();
+            msg::reply(StateOutput::LeavesPage { res }, 0).expect("Failed to share state");
+        }
+        StatePayload::FixedTree => {
+            let FixedTree { filled, root, len } = mixer.fixed_tree;
+            msg::reply(StateOutput::FixedTree { filled, root, leaves_len: len }, 0)
+                .expect("Failed to share state");
+        }
+        StatePayload::KnownRoot { root, tree } => {
+            let res = mixer.is_known_root(root, tree);
+            msg::reply(StateOutput::KnownRoot { res }, 0).expect("Failed to share state");
+        }
     }
 }
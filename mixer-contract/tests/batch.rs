@@ -0,0 +1,100 @@
+//! Queued deposits inserted by a dev-mode batch proof, which has to carry the
+//! fixed-depth tree along with the append-only one.
+
+mod common;
+
+use common::*;
+use gtest::{Log, System};
+use io::{
+    zero_hashes, BatchOutputs, ContractHandleAction, ContractHandleEvent, FixedTree, Frontier, RustShit, StateOutput,
+    StatePayload, DEPOSIT_AMOUNT,
+};
+use mixer_contract::{guest_ids::BATCH_GUEST_ID, MerkleHasher};
+use risc0_zkvm::sha::{Impl, Sha256};
+
+fn enqueue(mixer: &gtest::Program, notes: &[RustShit]) {
+    let hashes = notes.iter().map(leaf).collect();
+    let value = notes.len() as u128 * DEPOSIT_AMOUNT;
+    assert!(!mixer.send_with_value(USER, ContractHandleAction::Enqueue { hashes }, value).main_failed());
+}
+
+/// What the batch guest commits for appending `queued` after `inserted`,
+/// along with the fixed tree it leaves behind.
+fn batch(inserted: &[RustShit], queued: &[RustShit]) -> (BatchOutputs, FixedTree) {
+    let zeros = zero_hashes::<MerkleHasher>();
+    let mut frontier = Frontier::default();
+    let mut fixed_tree = FixedTree::default();
+    for note in inserted {
+        frontier.append::<MerkleHasher>(leaf(note));
+        fixed_tree.append::<MerkleHasher>(&zeros, leaf(note));
+    }
+    let old_root = frontier.root::<MerkleHasher>();
+    let old_len = frontier.len;
+    let old_fixed = fixed_tree.commitment::<MerkleHasher>();
+
+    let commitments: Vec<[u8; 32]> = queued.iter().map(leaf).collect();
+    for commitment in &commitments {
+        frontier.append::<MerkleHasher>(*commitment);
+        fixed_tree.append::<MerkleHasher>(&zeros, *commitment);
+    }
+    let outputs = BatchOutputs {
+        old_root,
+        old_len,
+        count: commitments.len() as u64,
+        batch_hash: (*Impl::hash_bytes(&commitments.concat())).into(),
+        new_root: frontier.root::<MerkleHasher>(),
+        new_peaks: frontier.peaks,
+        old_fixed,
+        new_fixed_root: fixed_tree.root(&zeros),
+        new_filled: fixed_tree.filled.clone(),
+    };
+    (outputs, fixed_tree)
+}
+
+fn insert_batch(mixer: &gtest::Program, outputs: &BatchOutputs) -> gtest::RunResult {
+    let image_id_receipt = dev_mode_receipt(BATCH_GUEST_ID, outputs.to_journal());
+    mixer.send(USER, ContractHandleAction::InsertBatch { image_id_receipt })
+}
+
+#[test]
+fn batch_updates_fixed_tree() {
+    let sys = System::new();
+    let mixer = setup_dev_mode(&sys);
+    let notes = notes(5);
+    deposit(&mixer, &notes[..2]);
+    enqueue(&mixer, &notes[2..]);
+
+    let (outputs, fixed_tree) = batch(&notes[..2], &notes[2..]);
+    let inserted = Log::builder().dest(USER).payload(ContractHandleEvent::BatchInserted);
+    assert!(insert_batch(&mixer, &outputs).contains(&inserted));
+
+    // What `insert-batch` starts the next proof from.
+    let FixedTree { filled, root, len } = fixed_tree;
+    let state: StateOutput = mixer.read_state(StatePayload::FixedTree).unwrap();
+    assert_eq!(state, StateOutput::FixedTree { filled, root, leaves_len: len });
+
+    // Batched notes are spendable against the fixed tree the proof committed.
+    assert!(!withdraw(&mixer, prove_fixed(&notes, &notes[4..])).main_failed());
+    assert_eq!(spent(&mixer, &notes), [false, false, false, false, true]);
+}
+
+#[test]
+fn batch_against_another_fixed_tree_rejected() {
+    let sys = System::new();
+    let mixer = setup_dev_mode(&sys);
+    let notes = notes(5);
+    deposit(&mixer, &notes[..3]);
+    enqueue(&mixer, &notes[3..]);
+
+    // Same root and length, but `filled` the next appends would build on is forged.
+    let (mut outputs, _) = batch(&notes[..3], &notes[3..]);
+    let mut forged = FixedTree::default();
+    let zeros = zero_hashes::<MerkleHasher>();
+    notes[..3].iter().for_each(|note| forged.append::<MerkleHasher>(&zeros, leaf(note)));
+    forged.filled[1] = [0xee; 32];
+    outputs.old_fixed = forged.commitment::<MerkleHasher>();
+    assert!(insert_batch(&mixer, &outputs).main_failed());
+
+    let (outputs, _) = batch(&notes[..3], &notes[3..]);
+    assert!(!insert_batch(&mixer, &outputs).main_failed());
+}
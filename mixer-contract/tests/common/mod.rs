@@ -0,0 +1,194 @@
+//! gtest harness: the `Mixer` program next to a mock of the node's RISC0
+//! builtin that accepts `RISC0_DEV_MODE` fake receipts and replies with their
+//! journals, so withdrawals run without the `risc0-builtin` node fork. The
+//! receipts come from executing the withdraw guest, except for journals the
+//! guest would never commit.
+
+#![allow(dead_code)]
+
+use gtest::{Program, RunResult, System, WasmProgram};
+use io::{
+    rs_merkle::{Hasher, MerkleTree},
+    ActorId, ContractHandleAction, Frontier, MerklePath, PublicOutputs, ReceiptKind, ReceiptPolicy, RustShit,
+    StateOutput, StatePayload, WithdrawInput, DEPOSIT_AMOUNT,
+};
+use methods::HELLO_GUEST_ELF;
+use mixer_contract::{guest_ids::GUEST_ID, MerkleHasher};
+use risc0_zkvm::{default_executor, ExecutorEnv, FakeReceipt, InnerReceipt, Receipt, ReceiptClaim};
+
+pub const ADMIN: u64 = 100;
+pub const USER: u64 = 101;
+pub const RECIPIENT: u64 = 102;
+
+pub const RISC0_BUILTIN: [u8; 32] =
+    hex_literal::hex!("1ef25efb2be22235d221e0570bf57efd2b5483a39088cff6e9144b1125696632");
+
+#[derive(Debug)]
+struct MockBuiltin;
+
+impl WasmProgram for MockBuiltin {
+    fn init(&mut self, _payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        Ok(None)
+    }
+
+    fn handle(&mut self, payload: Vec<u8>) -> Result<Option<Vec<u8>>, &'static str> {
+        let (image_id, receipt): ([u32; 8], Receipt) =
+            postcard::from_bytes(&payload).map_err(|_| "Malformed image id and receipt")?;
+        if !matches!(receipt.inner, InnerReceipt::Fake(_)) {
+            return Err("Mock builtin only accepts dev-mode receipts");
+        }
+        receipt.verify(image_id).map_err(|_| "Receipt doesn't verify")?;
+        Ok(Some(receipt.journal.bytes))
+    }
+
+    fn handle_reply(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn handle_signal(&mut self, _payload: Vec<u8>) -> Result<(), &'static str> {
+        Ok(())
+    }
+
+    fn state(&mut self) -> Result<Vec<u8>, &'static str> {
+        Ok(Vec::new())
+    }
+}
+
+/// A fresh system with the mixer initialized by `ADMIN` under the default
+/// receipt policy, the mock builtin in place and `USER` funded for a handful
+/// of deposits.
+pub fn setup(sys: &System) -> Program<'_> {
+    // Fake receipts only verify in dev mode.
+    std::env::set_var("RISC0_DEV_MODE", "1");
+    sys.init_logger();
+
+    let builtin = Program::mock_with_id(sys, RISC0_BUILTIN, MockBuiltin);
+    assert!(!builtin.send_bytes(ADMIN, []).main_failed());
+
+    let mixer = Program::current(sys);
+    assert!(!mixer.send_bytes(ADMIN, []).main_failed());
+
+    sys.mint_to(USER, 100 * DEPOSIT_AMOUNT);
+    mixer
+}
+
+/// [`setup`] with a policy that lets the mock's dev-mode receipts through.
+/// No deployment should accept them, so only the tests that withdraw opt in.
+pub fn setup_dev_mode(sys: &System) -> Program<'_> {
+    let mixer = setup(sys);
+    let policy = ReceiptPolicy {
+        accepted_kinds: vec![ReceiptKind::Fake],
+        ..Default::default()
+    };
+    assert!(!set_receipt_policy(&mixer, ADMIN, policy).main_failed());
+    mixer
+}
+
+pub fn set_receipt_policy(mixer: &Program, from: u64, policy: ReceiptPolicy) -> RunResult {
+    mixer.send(from, ContractHandleAction::SetReceiptPolicy { policy })
+}
+
+pub fn notes(count: u8) -> Vec<RustShit> {
+    (0..count).map(|i| RustShit([i + 1; 32], [i + 101; 32])).collect()
+}
+
+pub fn leaf(note: &RustShit) -> [u8; 32] {
+    MerkleHasher::hash(&note.to_preimage())
+}
+
+/// Root the contract records after `notes` are deposited into an empty mixer.
+pub fn root(notes: &[RustShit]) -> [u8; 32] {
+    let mut frontier = Frontier::default();
+    notes.iter().for_each(|note| frontier.append::<MerkleHasher>(leaf(note)));
+    frontier.root::<MerkleHasher>()
+}
+
+pub fn deposit(mixer: &Program, notes: &[RustShit]) -> RunResult {
+    let hashes = notes.iter().map(leaf).collect();
+    mixer.send_with_value(
+        USER,
+        ContractHandleAction::Deposit { hashes },
+        notes.len() as u128 * DEPOSIT_AMOUNT,
+    )
+}
+
+/// What `default_prover()` returns with `RISC0_DEV_MODE=1`: a receipt whose
+/// claim is taken on trust, paired with the image id the way `Withdraw` expects.
+pub fn dev_mode_receipt(image_id: [u32; 8], journal: Vec<u8>) -> Vec<u8> {
+    let claim = ReceiptClaim::ok(image_id, journal.clone());
+    let receipt = Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim)), journal);
+    postcard::to_allocvec(&(image_id, receipt)).expect("Receipt serializes")
+}
+
+/// Dev-mode withdraw receipt for a journal the guest wouldn't commit, such
+/// as one with a forged root, under the accepted image id.
+pub fn fake_withdrawal(outputs: &PublicOutputs) -> Vec<u8> {
+    dev_mode_receipt(GUEST_ID, outputs.to_journal())
+}
+
+/// What `default_prover()` returns with `RISC0_DEV_MODE=1` for the withdraw
+/// guest spending `spent` out of `deposited` with a multiproof. The receipt
+/// claims the image id the guest was run as, so it only verifies if the
+/// contract's `GUEST_ID` is the guest's.
+pub fn prove(deposited: &[RustShit], spent: &[RustShit]) -> Vec<u8> {
+    let leaves: Vec<[u8; 32]> = deposited.iter().map(leaf).collect();
+    let indices = indices(deposited, spent);
+    let proved: Vec<usize> = indices.iter().map(|&index| index as usize).collect();
+    let proof_bytes = MerkleTree::<MerkleHasher>::from_leaves(&leaves).proof(&proved).to_bytes();
+    run_guest(WithdrawInput::new(proof_bytes, indices, spent.to_vec(), leaves.len() as u64))
+}
+
+/// [`prove`] with fixed-depth paths into the tree of `deposited`.
+pub fn prove_fixed(deposited: &[RustShit], spent: &[RustShit]) -> Vec<u8> {
+    let leaves: Vec<[u8; 32]> = deposited.iter().map(leaf).collect();
+    let paths = indices(deposited, spent)
+        .into_iter()
+        .map(|index| MerklePath::build::<MerkleHasher>(&leaves, index))
+        .collect();
+    run_guest(WithdrawInput::with_paths(paths, spent.to_vec()))
+}
+
+fn indices(deposited: &[RustShit], spent: &[RustShit]) -> Vec<u64> {
+    spent
+        .iter()
+        .map(|note| deposited.iter().position(|deposited| deposited == note).expect("Spent notes are deposited") as u64)
+        .collect()
+}
+
+fn run_guest(input: WithdrawInput) -> Vec<u8> {
+    input.validate().expect("Withdraw input is valid");
+    let env = ExecutorEnv::builder().write(&input).unwrap().build().unwrap();
+    let session = default_executor().execute(env, HELLO_GUEST_ELF).expect("Withdraw guest runs");
+    let claim = session.receipt_claim.expect("Session has a claim");
+    let receipt = Receipt::new(InnerReceipt::Fake(FakeReceipt::new(claim)), session.journal.bytes);
+    postcard::to_allocvec(&(GUEST_ID, receipt)).expect("Receipt serializes")
+}
+
+pub fn withdraw(mixer: &Program, image_id_receipt: Vec<u8>) -> RunResult {
+    mixer.send(USER, ContractHandleAction::Withdraw { image_id_receipt })
+}
+
+/// Notes paid out by each of `USER`'s withdrawals, as its history records them.
+pub fn payouts(mixer: &Program) -> Vec<u32> {
+    let payload = StatePayload::HistoryOneFrom {
+        user: ActorId::from(USER),
+        from: 0,
+    };
+    match mixer.read_state(payload).expect("History is readable") {
+        StateOutput::HistoryOneFrom { res } => res
+            .iter()
+            .filter(|info| info.amount <= 0)
+            .map(|info| info.amount.unsigned_abs())
+            .collect(),
+        other => panic!("Unexpected state {other:?}"),
+    }
+}
+
+/// Whether each note's nullifier has been spent.
+pub fn spent(mixer: &Program, notes: &[RustShit]) -> Vec<bool> {
+    let nullifiers = notes.iter().map(|note| note.0).collect();
+    match mixer.read_state(StatePayload::IsSpent { nullifiers }).expect("Nullifiers are readable") {
+        StateOutput::IsSpent { res } => (0..notes.len()).map(|i| res[i / 8] & (1 << (i % 8)) != 0).collect(),
+        other => panic!("Unexpected state {other:?}"),
+    }
+}
@@ -0,0 +1,143 @@
+//! Moving a mixer's state and balance to a successor program: freeze, export
+//! every section, import it, then hand over.
+
+mod common;
+
+use common::*;
+use gtest::{Program, RunResult, System};
+use io::{
+    ActorId, ContractHandleAction, ContractHandleEvent, ReceiptKind, ReceiptPolicy, StateChunk, StateOutput,
+    StatePayload, StateSection, DEPOSIT_AMOUNT,
+};
+use parity_scale_codec::Decode;
+
+const PAGE: u32 = 2;
+
+fn actor(program: &Program) -> ActorId {
+    ActorId::new(program.id().into_bytes())
+}
+
+fn send(mixer: &Program, action: ContractHandleAction) -> RunResult {
+    mixer.send(ADMIN, action)
+}
+
+/// Every page of `section`, as `ImportState` takes them.
+fn export(mixer: &Program, section: StateSection) -> Vec<StateChunk> {
+    let mut chunks = vec![];
+    let mut from = 0;
+    loop {
+        let result = send(mixer, ContractHandleAction::ExportState { section, from, limit: PAGE });
+        let reply = result.log().iter().find(|log| log.destination() == ADMIN.into()).expect("Export replies");
+        let ContractHandleEvent::StateExported { chunk, total } = Decode::decode(&mut reply.payload()).unwrap() else {
+            panic!("Export failed");
+        };
+        chunks.push(chunk);
+        from += PAGE as u64;
+        if from >= total {
+            return chunks;
+        }
+    }
+}
+
+fn migrate(from: &Program, to: &Program, sections: &[StateSection]) {
+    assert!(!send(to, ContractHandleAction::BeginImport { predecessor: actor(from) }).main_failed());
+    for &section in sections {
+        for chunk in export(from, section) {
+            assert!(!send(to, ContractHandleAction::ImportState { chunk }).main_failed());
+        }
+    }
+}
+
+/// Another mixer next to the one [`setup`] made, taking dev-mode receipts.
+fn successor(sys: &System) -> Program<'_> {
+    let mixer = Program::current(sys);
+    assert!(!mixer.send_bytes(ADMIN, []).main_failed());
+    let policy = ReceiptPolicy {
+        accepted_kinds: vec![ReceiptKind::Fake],
+        ..Default::default()
+    };
+    assert!(!set_receipt_policy(&mixer, ADMIN, policy).main_failed());
+    mixer
+}
+
+fn importing_from(mixer: &Program) -> Option<ActorId> {
+    match mixer.read_state(StatePayload::Migration).unwrap() {
+        StateOutput::Migration { importing_from, .. } => importing_from,
+        other => panic!("Unexpected state {other:?}"),
+    }
+}
+
+/// A mixer with three notes deposited in two transactions and the first spent.
+fn predecessor(sys: &System) -> (Program<'_>, Vec<io::RustShit>) {
+    let mixer = setup_dev_mode(sys);
+    let notes = notes(3);
+    deposit(&mixer, &notes[..2]);
+    deposit(&mixer, &notes[2..]);
+    assert!(!withdraw(&mixer, prove(&notes, &notes[..1])).main_failed());
+    (mixer, notes)
+}
+
+#[test]
+fn export_requires_freeze() {
+    let sys = System::new();
+    let (mixer, notes) = predecessor(&sys);
+    let export = ContractHandleAction::ExportState {
+        section: StateSection::Leaves,
+        from: 0,
+        limit: PAGE,
+    };
+    assert!(send(&mixer, export.clone()).main_failed());
+    assert!(send(&mixer, ContractHandleAction::Handover { successor: USER.into() }).main_failed());
+
+    assert!(mixer.send(USER, ContractHandleAction::Freeze).main_failed());
+    assert!(!send(&mixer, ContractHandleAction::Freeze).main_failed());
+    assert!(!send(&mixer, export).main_failed());
+
+    // Nothing may change what has been exported.
+    assert!(deposit(&mixer, &notes[..1]).main_failed());
+    assert!(withdraw(&mixer, prove(&notes, &notes[1..2])).main_failed());
+}
+
+#[test]
+fn handover_moves_state_and_value() {
+    let sys = System::new();
+    let (old, notes) = predecessor(&sys);
+    assert!(!send(&old, ContractHandleAction::Freeze).main_failed());
+
+    let new = successor(&sys);
+    let sections = [
+        StateSection::Leaves,
+        StateSection::Roots,
+        StateSection::Nullifiers,
+        StateSection::FixedRoots,
+    ];
+    migrate(&old, &new, &sections);
+    let handover = send(&old, ContractHandleAction::Handover { successor: actor(&new) });
+    assert!(!handover.main_failed() && !handover.others_failed());
+    assert_eq!(importing_from(&new), None);
+    assert!(new.balance() >= 2 * DEPOSIT_AMOUNT);
+
+    // Spent stays spent, and proofs against the predecessor's older roots still go through.
+    assert_eq!(spent(&new, &notes), [true, false, false]);
+    assert!(!withdraw(&new, prove_fixed(&notes[..2], &notes[1..2])).main_failed());
+    assert_eq!(spent(&new, &notes), [true, true, false]);
+}
+
+#[test]
+fn incomplete_import_rejected() {
+    let sys = System::new();
+    let (old, _) = predecessor(&sys);
+    assert!(!send(&old, ContractHandleAction::Freeze).main_failed());
+
+    // Missing nullifiers would reopen spent notes, and missing fixed roots would
+    // turn down proofs against them.
+    for sections in [
+        &[StateSection::Leaves, StateSection::Roots, StateSection::FixedRoots][..],
+        &[StateSection::Leaves, StateSection::Roots, StateSection::Nullifiers],
+    ] {
+        let new = successor(&sys);
+        migrate(&old, &new, sections);
+        assert!(send(&old, ContractHandleAction::Handover { successor: actor(&new) }).others_failed());
+        assert_eq!(importing_from(&new), Some(actor(&old)));
+    }
+}
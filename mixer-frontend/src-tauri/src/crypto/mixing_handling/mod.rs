@@ -1,18 +1,24 @@
 use std::error::Error;
 
-use gclient::ext::sp_core::hashing::sha2_256;
 use keyring::Entry;
+use mixer_client::MixerClient;
+use mixer_protocol::{rs_merkle::Hasher, ReceiptBundle};
 use parity_scale_codec::{Decode, Encode};
-use utils::{make_proof, ContractHandleAction, StateOutput, StatePayload};
+use utils::{make_bundle, make_input, make_ownership_input, prove_aggregate_locally, prove_locally, prove_ownership_locally, prove_remotely, save_bundle, save_bundle_as, save_witness, verify_attestation, verify_bundle, MerkleHasher};
 
-use crate::{ACCOUNTS, CONTRACT, DERIVED_KEY, KEYRING_SERVICE, MIXING, SALT};
+use crate::{ACCOUNTS, CONTRACT, DERIVED_KEY, KEYRING_SERVICE, MIXING, PROVER, PROVER_TOKEN, PROVER_URL, SALT, SAVE_WITNESSES};
 
 use super::account_handling::{self, utils::{decrypt_string, encrypt_bytes_with_salt_and_derived_key}};
 
 mod utils;
 mod methods;
 
-const VARA_UNIT: u128 = 1_000_000_000_000;
+pub use utils::{AttestationInfo, BundleInfo, ProverSettings, WalletConfig};
+
+async fn client(addr: &str) -> MixerClient {
+    let gear_api = ACCOUNTS.lock().await.get(addr).unwrap().clone();
+    MixerClient::new(gear_api, CONTRACT.0.into())
+}
 
 pub async fn activate_mixing(indexes: Vec<u32>) -> Result<(), Box<dyn Error>>{
     let mut guard = MIXING.lock().await;
@@ -43,7 +49,6 @@ pub async fn check_mixing(data: Vec<[u8; 32]>) -> Result<(u32, Vec<u32>), Box<dy
 }
 
 pub async fn deposit(addr: String, amount: u32, mut shift: u32) -> Result<Vec<u32>, Box<dyn Error>>{
-    let contract = CONTRACT;
     if amount % 10 != 0{
         Err("Wrong amount, must be amount % 10")?;
     }
@@ -53,28 +58,15 @@ pub async fn deposit(addr: String, amount: u32, mut shift: u32) -> Result<Vec<u3
     for _ in 0..size{
         let d: [u8; 64] = sodiumoxide::randombytes::randombytes(64).try_into().unwrap();
         data.push((d[..32].try_into().unwrap(), d));
-        let hd = sha2_256(&d);
+        let hd = MerkleHasher::hash(&d);
         hash_data.push(hd);
     }
-    let payload = ContractHandleAction::Deposit { hashes: hash_data }.encode(); 
-
-    let gear_api;
-    {
-        let guard = ACCOUNTS.lock().await;
-        gear_api = guard.get(&addr).unwrap().clone();
-    }
-
-    let value = amount as u128 * VARA_UNIT;
 
-    let gas_info = gear_api.calculate_handle_gas(None, contract.into(), payload.clone(), value, true).await?;
-    let balance = gear_api.free_balance(gear_api.account_id()).await?;
-    if gas_info.min_limit as u128 + value > balance{
-        Err("Insufficient balance")?;
-    }
+    let client = client(&addr).await;
 
     let mut guard = MIXING.lock().await;
 
-    gear_api.send_message_bytes(contract.into(), payload, gas_info.min_limit, value).await?;
+    client.deposit(hash_data).await?;
 
     let derived_key = *DERIVED_KEY.lock().await;
     
@@ -90,8 +82,6 @@ pub async fn deposit(addr: String, amount: u32, mut shift: u32) -> Result<Vec<u3
 }
 
 pub async fn withdraw(addr: String, amount: u32) -> Result<(), Box<dyn Error>>{
-    let contract = CONTRACT;
-
     if amount % 10 != 0{
         Err("Wrong amount, must be amount % 10")?;
     }
@@ -102,33 +92,104 @@ pub async fn withdraw(addr: String, amount: u32) -> Result<(), Box<dyn Error>>{
     }
     let taken_elements: Vec<[u8; 64]> = guard.iter().take(size as usize).map(|(_, &v)| v.0).collect();
 
-    let gear_api;
-    {
-        let guard = ACCOUNTS.lock().await;
-        gear_api = guard.get(&addr).unwrap().clone();
-    }
+    let client = client(&addr).await;
+
+    let leaves = client.leaves().await?;
 
-    let output: StateOutput = gear_api.read_state(contract.into(), StatePayload::Leaves.encode()).await?;
-    let leaves = match output {
-        StateOutput::Leaves { res } => res,
-        _ => unreachable!(),
+    let input = make_input(&taken_elements, &leaves)?;
+    // The witness holds note secrets, so it's encrypted like exported notes.
+    let witness = match *SAVE_WITNESSES.lock().await {
+        true => Some(save_witness(&encrypt_bytes_with_salt_and_derived_key(&serde_json::to_vec(&input)?, &*SALT.lock().await, &*DERIVED_KEY.lock().await)?)?),
+        false => None,
     };
+    let with_witness = |e: Box<dyn Error>| match &witness {
+        Some(path) => format!("{e}; the witness is saved to {}", path.display()).into(),
+        None => e,
+    };
+
+    let prover_url = PROVER_URL.lock().await.clone();
+    let prover_token = PROVER_TOKEN.lock().await.clone().unwrap_or_default();
+    let settings = PROVER.lock().await.clone();
+    let image_id_receipt = match prover_url {
+        Some(_) if settings.aggregate => Err("The prover daemon doesn't aggregate, clear its URL to aggregate locally".into()),
+        Some(url) => prove_remotely(&url, &prover_token, &input).await,
+        None if settings.aggregate => prove_aggregate_locally(&taken_elements, &leaves, &settings),
+        None => prove_locally(&input, &settings),
+    }.map_err(with_witness)?;
+
+    let bundle = make_bundle(image_id_receipt, CONTRACT.0.into())?;
+    let path = save_bundle(&bundle)?;
+    client
+        .withdraw(bundle.image_id_receipt)
+        .await
+        .map_err(|e| format!("{e}; the receipt is saved to {} and can be submitted later", path.display()))?;
+    std::fs::remove_file(path)?;
+    if let Some(witness) = witness {
+        std::fs::remove_file(witness)?;
+    }
 
-    let image_id_receipt = make_proof(&taken_elements, &leaves)?;
+    Ok(())
+}
+
+pub async fn verify_receipt_bundle(bundle: Vec<u8>) -> Result<BundleInfo, Box<dyn Error>>{
+    let bundle = ReceiptBundle::from_bytes(&bundle)?;
+    let outputs = verify_bundle(&bundle)?;
 
-    let payload = ContractHandleAction::Withdraw { image_id_receipt }.encode(); 
+    Ok(BundleInfo::new(&bundle, &outputs))
+}
 
-    let gas_info = gear_api.calculate_handle_gas(None, contract.into(), payload.clone(), 0, true).await?;
-    let balance = gear_api.free_balance(gear_api.account_id()).await?;
-    if gas_info.min_limit as u128 > balance{
-        Err("Insufficient balance")?;
+pub async fn submit_receipt_bundle(addr: String, bundle: Vec<u8>) -> Result<(), Box<dyn Error>>{
+    let bundle = ReceiptBundle::from_bytes(&bundle)?;
+    verify_bundle(&bundle)?;
+    if bundle.contract != CONTRACT.0.into() {
+        Err("Receipt was made for another contract")?;
     }
 
-    gear_api.send_message_bytes(contract.into(), payload, gas_info.min_limit, 0).await?;
+    client(&addr).await.withdraw(bundle.image_id_receipt).await?;
 
     Ok(())
 }
 
+/// Proves ownership of the note with `commitment`, or of any note, without
+/// spending it, and returns where the attestation was saved. With a
+/// `blocklist` of flagged leaf indices the note must not be one of them.
+pub async fn attest_ownership(addr: String, commitment: Option<String>, challenge: String, disclose: bool, blocklist: Option<Vec<u64>>) -> Result<String, Box<dyn Error>>{
+    let challenge: [u8; 32] = hex::decode(challenge.trim_start_matches("0x"))?.try_into().map_err(|_| "Challenge must be 32 bytes")?;
+    let commitment: Option<[u8; 32]> = match commitment {
+        Some(commitment) => Some(hex::decode(commitment.trim_start_matches("0x"))?.try_into().map_err(|_| "Commitment must be 32 bytes")?),
+        None => None,
+    };
+    let elem = MIXING
+        .lock()
+        .await
+        .values()
+        .map(|(elem, _)| *elem)
+        .find(|elem| commitment.map_or(true, |commitment| MerkleHasher::hash(elem) == commitment))
+        .ok_or("No such note")?;
+
+    let leaves = client(&addr).await.leaves().await?;
+    let input = make_ownership_input(elem, &leaves, challenge, disclose, blocklist)?;
+    let bundle = make_bundle(prove_ownership_locally(&input, &PROVER.lock().await.clone())?, CONTRACT.0.into())?;
+
+    Ok(save_bundle_as(&bundle, "attestation")?.display().to_string())
+}
+
+/// Checks an attestation and asks the contract it names whether its root was
+/// ever recorded, which a valid receipt alone doesn't show.
+pub async fn verify_ownership_attestation(addr: String, bundle: Vec<u8>, challenge: Option<String>) -> Result<AttestationInfo, Box<dyn Error>>{
+    let bundle = ReceiptBundle::from_bytes(&bundle)?;
+    let outputs = verify_attestation(&bundle)?;
+    if challenge.is_some_and(|challenge| challenge.trim_start_matches("0x") != hex::encode(outputs.challenge)) {
+        Err("Attestation answers another challenge")?;
+    }
+    let gear_api = ACCOUNTS.lock().await.get(&addr).ok_or("No such account")?.clone();
+    if !MixerClient::new(gear_api, bundle.contract).is_known_root(outputs.root, outputs.tree).await? {
+        Err("Attestation is against a root its contract never recorded")?;
+    }
+
+    Ok(AttestationInfo::new(&bundle, &outputs))
+}
+
 pub async fn export_mixing(amount: u32) -> Result<String, Box<dyn Error>>{
     if amount % 10 != 0{
         Err("Wrong amount, must be amount % 10")?;
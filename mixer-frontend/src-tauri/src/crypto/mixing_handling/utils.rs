@@ -1,84 +1,20 @@
-use std::error::Error;
-
-use gclient::{ext::sp_core::hashing::sha2_256, metadata::runtime_types::gprimitives::ActorId};
-use parity_scale_codec::{Decode, Encode};
-use risc0_zkvm::{default_prover, ExecutorEnv};
-use rs_merkle::MerkleTree;
-
-use super::methods::{HELLO_GUEST_ELF, HELLO_GUEST_ID};
-
-
-#[derive(Clone, Encode, Decode)]
-pub enum ContractHandleAction {
-    Deposit { hashes: Vec<[u8; 32]> },
-    Withdraw { image_id_receipt: Vec<u8> },
-}
-
-#[derive(Encode, Decode)]
-pub enum StatePayload {
-    Root,
-    Leaves,
-    Withdrawn{from: u64},
-    WithdrawnAll,
-    HistoryOneFrom{user: ActorId, from: u64},
-    HistoryFrom(Vec<(ActorId, u64)>),
-    HistoryAll,
-}
-
-#[derive(Encode, Decode, Clone)]
-pub struct TransactionInfo {
-    pub amount: i32,
-    pub time: u64,
-}
-
-#[derive(Encode, Decode)]
-pub enum StateOutput {
-    Root{
-        res: [u8; 32],
-    },
-    Leaves {
-        res: Vec<[u8; 32]>,
-    },
-    Withdrawn {
-        res: Vec<[u8; 32]>,
-    },
-    WithdrawnAll{
-        res: Vec<[u8; 32]>,
-    },
-    HistoryOneFrom{
-        res: Vec<TransactionInfo>,
-    },  
-    HistoryFrom {
-        res: Vec<(ActorId, Vec<TransactionInfo>)>,
-    },
-    HistoryAll {
-        res: Vec<(ActorId, Vec<TransactionInfo>)>,
-    },
-}
-
-#[derive(Clone)]
-struct DigestWrapper;
-
-impl rs_merkle::Hasher for DigestWrapper{
-    type Hash = [u8; 32];
-    fn hash(data: &[u8]) -> Self::Hash {
-        sha2_256(data)
-    }
-}
+use std::{error::Error, fs, path::PathBuf, time::{Duration, SystemTime}};
 
+use mixer_backend::{ProverConfig, ReceiptFormat};
+use mixer_protocol::{rs_merkle::{Hasher, MerkleTree}, ActorId, AggregateInput, AggregateOutputs, Blocklist, BlocklistCommitment, Membership, OwnershipInput, OwnershipOutputs, PublicOutputs, ReceiptBundle, ReceiptKind, RustShit, WithdrawInput};
+use risc0_zkvm::Receipt;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Clone, Copy)]
-#[repr(C)]
-pub struct RustShit(
-    pub [u8; 32],
-    pub [u8; 32],
-);
+use super::methods::{AGGREGATE_GUEST_ELF, AGGREGATE_GUEST_ID, HELLO_GUEST_ELF, HELLO_GUEST_ID, OWNERSHIP_GUEST_ELF, OWNERSHIP_GUEST_ID};
+
+pub use mixer_protocol::MerkleHasher;
+
+const PROVER_POLL_INTERVAL: Duration = Duration::from_secs(1);
 
 fn indices_elems_to_prove(elems: &[[u8; 64]], leaves: &[[u8; 32]]) -> (Vec<usize>, Vec<[u8; 64]>){
     let mut indices_elems_to_prove = Vec::with_capacity(elems.len());
     for elem in elems{
-        let sha_elem = sha2_256(&*elem);
+        let sha_elem = MerkleHasher::hash(&*elem);
         if let Some(pos) = leaves.iter().position(|x| *x == sha_elem){
             indices_elems_to_prove.push((pos, elem));
         }
@@ -96,18 +32,325 @@ fn indices_elems_to_prove(elems: &[[u8; 64]], leaves: &[[u8; 32]]) -> (Vec<usize
     (indices_to_prove, elems_to_prove)
 }
 
-pub fn make_proof(elems: &[[u8; 64]], leaves: &[[u8; 32]]) -> Result<Vec<u8>, Box<dyn Error>>{
+pub fn make_input(elems: &[[u8; 64]], leaves: &[[u8; 32]]) -> Result<WithdrawInput, Box<dyn Error>>{
     let (indices_to_prove, elems_to_prove) = indices_elems_to_prove(elems, leaves);
-    let tree = MerkleTree::<DigestWrapper>::from_leaves(leaves);
+    let tree = MerkleTree::<MerkleHasher>::from_leaves(leaves);
     let proof_bytes = tree.proof(&indices_to_prove).to_bytes();
 
-    let elems: Vec<RustShit> = elems_to_prove.iter().map(|a| RustShit(a[..32].try_into().unwrap(), a[32..].try_into().unwrap())).collect();
+    let notes: Vec<RustShit> = elems_to_prove.into_iter().map(RustShit::from).collect();
+    let input = WithdrawInput::new(proof_bytes, indices_to_prove.into_iter().map(|x| x as u64).collect(), notes, leaves.len() as u64);
+    input.validate()?;
+
+    Ok(input)
+}
+
+/// How in-process proofs are made. `default_prover()` reads this from
+/// environment variables, which desktop users can't set and the wallet can't
+/// safely change while it runs.
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProverSettings {
+    #[serde(flatten)]
+    pub prover: ProverConfig,
+    /// Prove each note on its own and aggregate the receipts into one.
+    #[serde(default)]
+    pub aggregate: bool,
+}
+
+/// Settings the wallet keeps across restarts, in `~/mixer_wallet.json`.
+#[derive(Serialize, Deserialize, Clone, Debug, Default)]
+pub struct WalletConfig {
+    pub prover_url: Option<String>,
+    #[serde(default)]
+    pub prover_token: Option<String>,
+    #[serde(default)]
+    pub prover: ProverSettings,
+    #[serde(default)]
+    pub save_witnesses: bool,
+}
+
+impl WalletConfig {
+    fn path() -> Result<PathBuf, Box<dyn Error>> {
+        Ok(tauri::api::path::home_dir().ok_or("No home directory")?.join("mixer_wallet.json"))
+    }
+
+    pub fn load() -> Result<Self, Box<dyn Error>> {
+        match fs::read(Self::path()?) {
+            Ok(bytes) => Ok(serde_json::from_slice(&bytes)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e)?,
+        }
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        fs::write(Self::path()?, serde_json::to_vec_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+pub fn prove_locally(input: &WithdrawInput, settings: &ProverSettings) -> Result<Vec<u8>, Box<dyn Error>>{
+    let env = settings.prover.env().write(input)?.build()?;
+    let receipt = settings.prover.prove(env, HELLO_GUEST_ELF)?;
+
+    Ok(postcard::to_allocvec(&(HELLO_GUEST_ID, receipt))?)
+}
+
+/// Proves each of `elems` on its own, as succinct receipts since only those
+/// resolve as assumptions, and aggregates them into one receipt.
+pub fn prove_aggregate_locally(elems: &[[u8; 64]], leaves: &[[u8; 32]], settings: &ProverSettings) -> Result<Vec<u8>, Box<dyn Error>>{
+    let config = &settings.prover;
+    if config.receipt == ReceiptFormat::Composite {
+        Err("Aggregate receipts can't be composite, pick a succinct or Groth16 receipt")?;
+    }
+    let note_config = ProverConfig { receipt: ReceiptFormat::Succinct, ..config.clone() };
+
+    let mut env = config.env();
+    let mut journals = Vec::with_capacity(elems.len());
+    for elem in elems {
+        let input = make_input(std::slice::from_ref(elem), leaves)?;
+        let receipt = note_config.prove(note_config.env().write(&input)?.build()?, HELLO_GUEST_ELF)?;
+        journals.push(receipt.journal.bytes.clone());
+        env.add_assumption(receipt);
+    }
+    let input = AggregateInput { note_image_id: HELLO_GUEST_ID, journals };
+    let receipt = config.prove(env.write(&input)?.build()?, AGGREGATE_GUEST_ELF)?;
+
+    Ok(postcard::to_allocvec(&(AGGREGATE_GUEST_ID, receipt))?)
+}
+
+/// Ownership proof input for `elem`, which must already be one of `leaves` and,
+/// if a blocklist is given, not at one of its flagged indices.
+pub fn make_ownership_input(elem: [u8; 64], leaves: &[[u8; 32]], challenge: [u8; 32], disclose: bool, blocklist: Option<Vec<u64>>) -> Result<OwnershipInput, Box<dyn Error>>{
+    let leaf = MerkleHasher::hash(&elem);
+    let index = leaves.iter().position(|x| *x == leaf).ok_or("The note is not in the pool yet")?;
+    let proof_bytes = MerkleTree::<MerkleHasher>::from_leaves(leaves).proof(&[index]).to_bytes();
+
+    let membership = Membership::MultiProof { proof_bytes, indices: vec![index as u64], total_leaves_count: leaves.len() as u64 };
+    let mut input = OwnershipInput::new(membership, RustShit::from(elem), challenge, disclose);
+    if let Some(mut flagged) = blocklist {
+        flagged.sort_unstable();
+        flagged.dedup();
+        let blocklist = Blocklist::Sorted(flagged);
+        if !blocklist.excludes::<MerkleHasher>(index as u64) {
+            Err("The note is on the blocklist")?;
+        }
+        input = input.excluding(blocklist);
+    }
+    input.validate()?;
+
+    Ok(input)
+}
+
+pub fn prove_ownership_locally(input: &OwnershipInput, settings: &ProverSettings) -> Result<Vec<u8>, Box<dyn Error>>{
+    let env = settings.prover.env().write(input)?.build()?;
+    let receipt = settings.prover.prove(env, OWNERSHIP_GUEST_ELF)?;
+
+    Ok(postcard::to_allocvec(&(OWNERSHIP_GUEST_ID, receipt))?)
+}
+
+#[derive(Deserialize)]
+struct Job {
+    id: String,
+}
+
+#[derive(Deserialize)]
+struct JobStatus {
+    state: String,
+    error: Option<String>,
+}
+
+/// Submits `input` to a `mixer-prover` daemon at `url`, which takes `token`,
+/// and waits for the receipt.
+pub async fn prove_remotely(url: &str, token: &str, input: &WithdrawInput) -> Result<Vec<u8>, Box<dyn Error>>{
+    let url = url.trim_end_matches('/');
+    let client = reqwest::Client::new();
+
+    let job: Job = client.post(format!("{url}/jobs")).bearer_auth(token).json(input).send().await?.error_for_status()?.json().await?;
+    let job_url = format!("{url}/jobs/{}", job.id);
+    loop {
+        let status: JobStatus = client.get(&job_url).bearer_auth(token).send().await?.error_for_status()?.json().await?;
+        match status.state.as_str() {
+            "done" => break,
+            "failed" => Err(format!("Prover failed: {}", status.error.unwrap_or_default()))?,
+            _ => tokio::time::sleep(PROVER_POLL_INTERVAL).await,
+        }
+    }
 
-    let env = ExecutorEnv::builder().write(&proof_bytes).unwrap().write(&indices_to_prove).unwrap().write(&elems).unwrap().write(&leaves.len()).unwrap().build().unwrap();
-    let prover = default_prover();
-    
-    let receipt = prover.prove(env, HELLO_GUEST_ELF).unwrap();
-    let image_id_receipt = postcard::to_allocvec(&(HELLO_GUEST_ID, receipt.receipt)).unwrap();
+    // The daemon drops the job once its receipt is fetched.
+    let image_id_receipt = client.get(format!("{job_url}/receipt")).bearer_auth(token).send().await?.error_for_status()?.bytes().await?.to_vec();
 
     Ok(image_id_receipt)
-}
\ No newline at end of file
+}
+
+pub fn make_bundle(image_id_receipt: Vec<u8>, contract: ActorId) -> Result<ReceiptBundle, Box<dyn Error>>{
+    let (image_id, receipt): ([u32; 8], Receipt) = postcard::from_bytes(&image_id_receipt)?;
+
+    Ok(ReceiptBundle {
+        image_id,
+        kind: ReceiptKind::of(&image_id_receipt).ok_or("Unknown receipt kind")?,
+        contract,
+        created_at: SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?.as_secs(),
+        journal: receipt.journal.bytes,
+        image_id_receipt,
+    })
+}
+
+/// Saves `bundle` under `~/mixer_receipts` so a failed submission can be retried.
+pub fn save_bundle(bundle: &ReceiptBundle) -> Result<PathBuf, Box<dyn Error>>{
+    save_bundle_as(bundle, "receipt")
+}
+
+/// Saves `bundle` under `~/mixer_receipts` as `<prefix>_<created at>.bin`.
+pub fn save_bundle_as(bundle: &ReceiptBundle, prefix: &str) -> Result<PathBuf, Box<dyn Error>>{
+    let dir = tauri::api::path::home_dir().ok_or("No home directory")?.join("mixer_receipts");
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{prefix}_{}.bin", bundle.created_at));
+    fs::write(&path, bundle.to_bytes())?;
+
+    Ok(path)
+}
+
+/// Saves an encrypted witness under `~/mixer_witnesses` for
+/// `mixer-cli prove --witness`, which reruns the guest on it.
+pub fn save_witness(encrypted: &str) -> Result<PathBuf, Box<dyn Error>>{
+    let dir = tauri::api::path::home_dir().ok_or("No home directory")?.join("mixer_witnesses");
+    fs::create_dir_all(&dir)?;
+    let created_at = SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?.as_secs();
+    let path = dir.join(format!("witness_{created_at}.txt"));
+    fs::write(&path, encrypted)?;
+
+    Ok(path)
+}
+
+/// Checks a bundle's receipt offline the way the contract does, short of the
+/// root and nullifier lookups.
+pub fn verify_bundle(bundle: &ReceiptBundle) -> Result<PublicOutputs, Box<dyn Error>>{
+    let (image_id, receipt): ([u32; 8], Receipt) = postcard::from_bytes(&bundle.image_id_receipt)?;
+    if image_id != bundle.image_id || receipt.journal.bytes != bundle.journal {
+        Err("Bundle does not match its receipt")?;
+    }
+    if image_id != HELLO_GUEST_ID && image_id != AGGREGATE_GUEST_ID {
+        Err("Receipt is for an unknown guest")?;
+    }
+    receipt.verify(image_id)?;
+
+    if image_id == AGGREGATE_GUEST_ID {
+        let aggregate = AggregateOutputs::from_journal(&bundle.journal)?;
+        if aggregate.note_image_id != HELLO_GUEST_ID {
+            Err("Aggregate receipt is built from unknown note proofs")?;
+        }
+        return Ok(aggregate.withdraw);
+    }
+    Ok(PublicOutputs::from_journal(&bundle.journal)?)
+}
+
+/// Checks an ownership attestation offline; whether its root is one of the
+/// contract's is left to whoever asked for it.
+pub fn verify_attestation(bundle: &ReceiptBundle) -> Result<OwnershipOutputs, Box<dyn Error>>{
+    let (image_id, receipt): ([u32; 8], Receipt) = postcard::from_bytes(&bundle.image_id_receipt)?;
+    if image_id != bundle.image_id || receipt.journal.bytes != bundle.journal {
+        Err("Bundle does not match its receipt")?;
+    }
+    if image_id != OWNERSHIP_GUEST_ID {
+        Err("Not an ownership attestation")?;
+    }
+    receipt.verify(image_id)?;
+
+    Ok(OwnershipOutputs::from_journal(&bundle.journal)?)
+}
+
+/// What the UI shows of an ownership attestation.
+#[derive(Serialize)]
+pub struct AttestationInfo {
+    contract: String,
+    created_at: u64,
+    root: String,
+    challenge: String,
+    leaf: Option<(u64, String)>,
+    blocklist: Option<String>,
+}
+
+impl AttestationInfo {
+    pub fn new(bundle: &ReceiptBundle, outputs: &OwnershipOutputs) -> Self {
+        Self {
+            contract: hex::encode(<[u8; 32]>::from(bundle.contract)),
+            created_at: bundle.created_at,
+            root: hex::encode(outputs.root),
+            challenge: hex::encode(outputs.challenge),
+            leaf: outputs.leaf.map(|leaf| (leaf.index, hex::encode(leaf.commitment))),
+            blocklist: outputs.blocklist.map(|commitment| match commitment {
+                BlocklistCommitment::Sorted(hash) => format!("list {}", hex::encode(hash)),
+                BlocklistCommitment::Sparse(root) => format!("tree {}", hex::encode(root)),
+            }),
+        }
+    }
+}
+
+/// What the UI shows of a receipt bundle.
+#[derive(Serialize)]
+pub struct BundleInfo {
+    contract: String,
+    created_at: u64,
+    image_id: String,
+    kind: String,
+    receipt_bytes: usize,
+    root: String,
+    nullifiers: Vec<String>,
+    call: Option<String>,
+    outputs: Vec<(String, u32)>,
+}
+
+impl BundleInfo {
+    pub fn new(bundle: &ReceiptBundle, outputs: &PublicOutputs) -> Self {
+        let image_id: Vec<u8> = bundle.image_id.iter().flat_map(|word| word.to_le_bytes()).collect();
+        Self {
+            contract: hex::encode(<[u8; 32]>::from(bundle.contract)),
+            created_at: bundle.created_at,
+            image_id: hex::encode(image_id),
+            kind: format!("{:?}", bundle.kind).to_lowercase(),
+            receipt_bytes: bundle.image_id_receipt.len(),
+            root: hex::encode(outputs.root),
+            nullifiers: outputs.used.iter().map(hex::encode).collect(),
+            call: outputs.call.map(hex::encode),
+            outputs: outputs.outputs.iter().map(|output| (hex::encode(output.recipient), output.note_count)).collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use mixer_backend::{ProverBackend, ProverConfig};
+    use mixer_protocol::test_vectors::{Fixtures, FIXTURES};
+    use risc0_zkvm::Receipt;
+
+    use super::{make_input, prove_locally, ProverSettings};
+
+    /// The wallet's own tree builder and embedded guest commit the journals
+    /// recorded in the fixtures.
+    #[test]
+    fn fixture_withdrawals() {
+        let fixtures: Fixtures = serde_json::from_str(FIXTURES).unwrap();
+        let leaves: Vec<[u8; 32]> = fixtures.notes.iter().map(|note| note.leaf).collect();
+        let settings = ProverSettings {
+            prover: ProverConfig { backend: ProverBackend::DevMode, ..Default::default() },
+            ..Default::default()
+        };
+        for withdrawal in fixtures.withdrawals.iter().filter(|withdrawal| !withdrawal.fixed) {
+            let elems: Vec<[u8; 64]> = withdrawal
+                .indices
+                .iter()
+                .map(|&i| {
+                    let note = &fixtures.notes[i as usize];
+                    [note.nullifier, note.secret].concat().try_into().unwrap()
+                })
+                .collect();
+            let input = make_input(&elems, &leaves[..withdrawal.size as usize]).unwrap();
+            let proved = prove_locally(&input, &settings).unwrap();
+            let (_, receipt): ([u32; 8], Receipt) = postcard::from_bytes(&proved).unwrap();
+            assert_eq!(
+                receipt.journal.bytes, withdrawal.journal,
+                "withdrawal of {:?} in {} leaves",
+                withdrawal.indices, withdrawal.size
+            );
+        }
+    }
+}
@@ -19,6 +19,65 @@ lazy_static! {
     static ref DERIVED_KEY: Mutex<[u8; 32]> = Mutex::new([0; 32]);
     static ref ACCOUNTS: Mutex<HashMap<String, GearApi>> = Mutex::new(HashMap::new());
     static ref MIXING: Mutex<HashMap<[u8; 32], ([u8; 64], u32)>> = Mutex::new(HashMap::new());
+    /// `mixer-prover` daemon to delegate proving to; proofs are made in-process when unset.
+    static ref PROVER_URL: Mutex<Option<String>> = Mutex::new(None);
+    /// Bearer token the daemon at `PROVER_URL` expects.
+    static ref PROVER_TOKEN: Mutex<Option<String>> = Mutex::new(None);
+    /// How proofs are made in-process.
+    static ref PROVER: Mutex<mixing_handling::ProverSettings> = Mutex::new(Default::default());
+    /// Debug mode: save every withdrawal's guest input, encrypted, until the withdrawal succeeds.
+    static ref SAVE_WITNESSES: Mutex<bool> = Mutex::new(false);
+}
+
+/// Writes the settings below to the wallet config so they survive a restart.
+async fn save_config() -> Result<(), String> {
+    mixing_handling::WalletConfig {
+        prover_url: PROVER_URL.lock().await.clone(),
+        prover_token: PROVER_TOKEN.lock().await.clone(),
+        prover: PROVER.lock().await.clone(),
+        save_witnesses: *SAVE_WITNESSES.lock().await,
+    }
+    .save()
+    .map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn set_prover_url(url: Option<String>, token: Option<String>) -> Result<(), String> {
+    *PROVER_URL.lock().await = url;
+    *PROVER_TOKEN.lock().await = token;
+    save_config().await
+}
+
+#[tauri::command]
+async fn get_prover_url() -> Option<String> {
+    PROVER_URL.lock().await.clone()
+}
+
+#[tauri::command]
+async fn get_prover_token() -> Option<String> {
+    PROVER_TOKEN.lock().await.clone()
+}
+
+#[tauri::command]
+async fn set_prover_settings(settings: mixing_handling::ProverSettings) -> Result<(), String> {
+    *PROVER.lock().await = settings;
+    save_config().await
+}
+
+#[tauri::command]
+async fn get_prover_settings() -> mixing_handling::ProverSettings {
+    PROVER.lock().await.clone()
+}
+
+#[tauri::command]
+async fn set_save_witnesses(enabled: bool) -> Result<(), String> {
+    *SAVE_WITNESSES.lock().await = enabled;
+    save_config().await
+}
+
+#[tauri::command]
+async fn get_save_witnesses() -> bool {
+    *SAVE_WITNESSES.lock().await
 }
 
 #[tauri::command]
@@ -36,6 +95,26 @@ async fn withdraw(addr: String, amount: u32) -> Result<(), String>{
     mixing_handling::withdraw(addr, amount).await.map_err(|e| e.to_string())
 }
 
+#[tauri::command]
+async fn verify_receipt_bundle(bundle: Vec<u8>) -> Result<mixing_handling::BundleInfo, String>{
+    mixing_handling::verify_receipt_bundle(bundle).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn submit_receipt_bundle(addr: String, bundle: Vec<u8>) -> Result<(), String>{
+    mixing_handling::submit_receipt_bundle(addr, bundle).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn attest_ownership(addr: String, commitment: Option<String>, challenge: String, disclose: bool, blocklist: Option<Vec<u64>>) -> Result<String, String>{
+    mixing_handling::attest_ownership(addr, commitment, challenge, disclose, blocklist).await.map_err(|e| e.to_string())
+}
+
+#[tauri::command]
+async fn verify_ownership_attestation(addr: String, bundle: Vec<u8>, challenge: Option<String>) -> Result<mixing_handling::AttestationInfo, String>{
+    mixing_handling::verify_ownership_attestation(addr, bundle, challenge).await.map_err(|e| e.to_string())
+}
+
 #[tauri::command]
 async fn deposit(addr: String, amount: u32, shift: u32) -> Result<Vec<u32>, String>{
     mixing_handling::deposit(addr, amount, shift).await.map_err(|e| e.to_string())
@@ -72,8 +151,18 @@ async fn get_all_accounts() -> Vec<String> {
 }
 
 fn main() {
+    match mixing_handling::WalletConfig::load() {
+        Ok(config) => tauri::async_runtime::block_on(async {
+            *PROVER_URL.lock().await = config.prover_url;
+            *PROVER_TOKEN.lock().await = config.prover_token;
+            *PROVER.lock().await = config.prover;
+            *SAVE_WITNESSES.lock().await = config.save_witnesses;
+        }),
+        Err(e) => eprintln!("Ignoring the wallet config: {e}"),
+    }
+
     tauri::Builder::default()
-        .invoke_handler(tauri::generate_handler![get_all_accounts, activate_accounts, create_new_account, export_account, import_account, check_mixing, deposit, withdraw, import_mixing, export_mixing])
+        .invoke_handler(tauri::generate_handler![get_all_accounts, activate_accounts, create_new_account, export_account, import_account, check_mixing, deposit, withdraw, import_mixing, export_mixing, set_prover_url, get_prover_url, get_prover_token, set_prover_settings, get_prover_settings, set_save_witnesses, get_save_witnesses, verify_receipt_bundle, submit_receipt_bundle, attest_ownership, verify_ownership_attestation])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
 }
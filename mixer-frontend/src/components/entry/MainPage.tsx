@@ -8,6 +8,24 @@ import { HexString } from '@gear-js/api';
 import { useMixingAmount } from '../../hooks/useMixingAmount';
 import UserButton from './UserButton';
 
+export interface ProverSettings {
+    backend: 'local' | 'dev_mode' | 'external';
+    r0vm: string | null;
+    segment_limit_po2: number | null;
+    cycle_limit: number | null;
+    receipt: 'composite' | 'succinct' | 'groth16';
+    aggregate: boolean;
+}
+
+const defaultProverSettings: ProverSettings = { backend: 'local', r0vm: null, segment_limit_po2: null, cycle_limit: null, receipt: 'succinct', aggregate: false };
+
+// Proofs go to the daemon when its URL is set, whatever the local backend.
+export const loadDevMode = async (): Promise<boolean> => {
+    const settings: ProverSettings = await invoke('get_prover_settings');
+    const url: string | null = await invoke('get_prover_url');
+    return settings.backend === 'dev_mode' && !url;
+};
+
 const MainPage: React.FC = () => {
     const [users, setUsers] = useState<IUser[]>([]);
     const [userName, setUserName] = useState('');
@@ -16,16 +34,48 @@ const MainPage: React.FC = () => {
     const [exportAmount, setExportAmount] = useState<number | undefined>(undefined);
     const [reinitMixingAmount, setReinitMixingAmount] = useState(true);
     const { mixingAmount, anonimityAmount } = useMixingAmount(reinitMixingAmount);
-    const [activeTab, setActiveTab] = useState<'addUser' | 'importUser' | 'exportMixing' | 'importAmount'>('addUser');
+    const [activeTab, setActiveTab] = useState<'addUser' | 'importUser' | 'exportMixing' | 'importAmount' | 'prover'>('addUser');
+    const [proverUrl, setProverUrl] = useState('');
+    const [proverToken, setProverToken] = useState('');
+    const [proverSettings, setProverSettings] = useState<ProverSettings>(defaultProverSettings);
+    const [saveWitnesses, setSaveWitnesses] = useState(false);
+    const [devMode, setDevMode] = useState(false);
 
     useEffect(() => {
         const init = async () => {
             const users0 = await getUsers();
             setUsers(users0);
+            // The backend keeps these in the wallet config.
+            const url: string | null = await invoke('get_prover_url');
+            setProverUrl(url || '');
+            setProverToken((await invoke<string | null>('get_prover_token')) || '');
+            setProverSettings({ ...defaultProverSettings, ...(await invoke<ProverSettings>('get_prover_settings')) });
+            setSaveWitnesses(await invoke<boolean>('get_save_witnesses'));
+            setDevMode(await loadDevMode());
         };
         void init();
     }, []);
 
+    const handleSetProver = async () => {
+        const url = proverUrl.trim();
+        try {
+            await invoke('set_prover_url', { url: url || null, token: proverToken.trim() || null });
+            await invoke('set_prover_settings', { settings: proverSettings });
+            await invoke('set_save_witnesses', { enabled: saveWitnesses });
+            setDevMode(proverSettings.backend === 'dev_mode' && !url);
+            if (url) {
+                alert(`Proofs will be made by ${url}`);
+            } else {
+                alert(proverSettings.backend === 'dev_mode'
+                    ? 'Proofs will be made locally as dev-mode receipts, which only dev-mode verifiers accept'
+                    : 'Proofs will be made locally');
+            }
+        } catch (e) {
+            console.error('Error in set_prover_url', e);
+            alert(`An error occurred while setting the prover: ${e}`);
+        }
+    };
+
     const handleAddUser = async () => {
         if (!userName) {
             alert('Please enter username');
@@ -187,7 +237,7 @@ const MainPage: React.FC = () => {
                 </div>
 
                 <div style={styles.formContainer}>
-                    {/* Tabs for Add User, Import User, Export Mixing, Import Amount */}
+                    {/* Tabs for Add User, Import User, Export Mixing, Import Amount, Prover */}
                     <div style={styles.tabContainer}>
                         <button onClick={() => setActiveTab('addUser')} style={activeTab === 'addUser' ? styles.activeTab : styles.tab}>
                             Add User
@@ -201,6 +251,9 @@ const MainPage: React.FC = () => {
                         <button onClick={() => setActiveTab('importAmount')} style={activeTab === 'importAmount' ? styles.activeTab : styles.tab}>
                             Import Amount
                         </button>
+                        <button onClick={() => setActiveTab('prover')} style={activeTab === 'prover' ? styles.activeTab : styles.tab}>
+                            Prover
+                        </button>
                     </div>
 
                     {/* Conditionally render the form based on activeTab */}
@@ -268,6 +321,85 @@ const MainPage: React.FC = () => {
                                 <button onClick={handleImportAmount} style={styles.button}>Import Amount</button>
                             </div>
                         )}
+
+                        {activeTab === 'prover' && (
+                            <div style={styles.exportMixingForm}>
+                                <input
+                                    type="text"
+                                    placeholder="mixer-prover URL, empty to prove locally"
+                                    value={proverUrl}
+                                    onChange={(e) => setProverUrl(e.target.value)}
+                                    style={styles.input}
+                                />
+                                {proverUrl.trim() && (
+                                    <input
+                                        type="password"
+                                        placeholder="mixer-prover token"
+                                        value={proverToken}
+                                        onChange={(e) => setProverToken(e.target.value)}
+                                        style={styles.input}
+                                    />
+                                )}
+                                <select
+                                    value={proverSettings.backend}
+                                    onChange={(e) => setProverSettings({ ...proverSettings, backend: e.target.value as ProverSettings['backend'] })}
+                                    style={styles.input}
+                                >
+                                    <option value="local">Local CPU</option>
+                                    <option value="dev_mode">Dev mode (fake receipts, testnets only)</option>
+                                    <option value="external">External r0vm</option>
+                                </select>
+                                {proverSettings.backend === 'external' && (
+                                    <input
+                                        type="text"
+                                        placeholder="r0vm path, empty to look it up on PATH"
+                                        value={proverSettings.r0vm ?? ''}
+                                        onChange={(e) => setProverSettings({ ...proverSettings, r0vm: e.target.value.trim() || null })}
+                                        style={styles.input}
+                                    />
+                                )}
+                                <input
+                                    type="number"
+                                    placeholder="Segment limit (log2 cycles), empty for default"
+                                    value={proverSettings.segment_limit_po2 ?? ''}
+                                    onChange={(e) => setProverSettings({ ...proverSettings, segment_limit_po2: e.target.value ? Number(e.target.value) : null })}
+                                    style={styles.input}
+                                />
+                                <input
+                                    type="number"
+                                    placeholder="Cycle limit, empty for none"
+                                    value={proverSettings.cycle_limit ?? ''}
+                                    onChange={(e) => setProverSettings({ ...proverSettings, cycle_limit: e.target.value ? Number(e.target.value) : null })}
+                                    style={styles.input}
+                                />
+                                <select
+                                    value={proverSettings.receipt}
+                                    onChange={(e) => setProverSettings({ ...proverSettings, receipt: e.target.value as ProverSettings['receipt'] })}
+                                    style={styles.input}
+                                >
+                                    <option value="succinct">Succinct receipt</option>
+                                    <option value="groth16">Groth16 receipt (needs Docker)</option>
+                                    <option value="composite">Composite receipt (large, usually rejected)</option>
+                                </select>
+                                <label style={styles.input}>
+                                    <input
+                                        type="checkbox"
+                                        checked={proverSettings.aggregate}
+                                        onChange={(e) => setProverSettings({ ...proverSettings, aggregate: e.target.checked })}
+                                    />
+                                    Prove notes one by one and aggregate them (local prover only)
+                                </label>
+                                <label style={styles.input}>
+                                    <input
+                                        type="checkbox"
+                                        checked={saveWitnesses}
+                                        onChange={(e) => setSaveWitnesses(e.target.checked)}
+                                    />
+                                    Keep encrypted witnesses of failed withdrawals (debug)
+                                </label>
+                                <button onClick={handleSetProver} style={styles.button}>Save</button>
+                            </div>
+                        )}
                     </div>
                 </div>
             </div>
@@ -276,6 +408,9 @@ const MainPage: React.FC = () => {
                 <div style={styles.topBar}>
                     <span style={styles.topBarItem}>Mixing amount: {mixingAmount !== undefined ? mixingAmount * 10 : undefined}</span>
                     <span style={styles.topBarItem}>Anonimity set: {anonimityAmount}</span>
+                    {devMode && (
+                        <span style={styles.devMode}>Dev mode: receipts are fake</span>
+                    )}
                 </div>
                 <Outlet /> {/* This will render the child route content */}
             </div>
@@ -394,6 +529,14 @@ const styles: { [key: string]: React.CSSProperties } = {
     topBarItem: {
         fontSize: '15px',
     },
+    devMode: {
+        fontSize: '15px',
+        fontWeight: 'bold',
+        color: '#ffffff',
+        backgroundColor: '#d9534f',
+        padding: '2px 8px',
+        borderRadius: '4px',
+    },
 };
 
 export default MainPage;
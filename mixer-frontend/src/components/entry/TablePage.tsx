@@ -1,10 +1,32 @@
-import React, { useState } from 'react';
+import React, { useState, useEffect } from 'react';
 import { useParams } from 'react-router-dom';
 import { invoke } from '@tauri-apps/api/tauri';
 import { addIndexes, getLastIndex } from '../../utils/IndexedDB';
 import { useStopwatch } from 'react-timer-hook';
 import { HexString } from '@gear-js/api';
 import TableTransactions from './TableTransactions';
+import { loadDevMode } from './MainPage';
+
+interface BundleInfo {
+  contract: string;
+  created_at: number;
+  image_id: string;
+  kind: string;
+  receipt_bytes: number;
+  root: string;
+  nullifiers: string[];
+  call: string | null;
+  outputs: [string, number][];
+}
+
+interface AttestationInfo {
+  contract: string;
+  created_at: number;
+  root: string;
+  challenge: string;
+  leaf: [number, string] | null;
+  blocklist: string | null;
+}
 
 const TablePage: React.FC = () => {
   const { userId } = useParams<{ userId: HexString }>();
@@ -12,8 +34,17 @@ const TablePage: React.FC = () => {
   const [withdrawAmount, setWithdrawAmount] = useState<number | null>(null);
   const [isSubmitting, setIsSubmitting] = useState(false);
   const [operation, setOperation] = useState<'deposit' | 'withdraw' | null>(null);
+  const [receiptFile, setReceiptFile] = useState<File | null>(null);
+  const [challenge, setChallenge] = useState('');
+  const [disclose, setDisclose] = useState(false);
+  const [flagged, setFlagged] = useState('');
 
   const { seconds, minutes, start, reset, pause } = useStopwatch({ autoStart: false });
+  const [devMode, setDevMode] = useState(false);
+
+  useEffect(() => {
+    void loadDevMode().then(setDevMode);
+  }, []);
 
   const handleDeposit = async () => {
     if (depositAmount === null) {
@@ -64,7 +95,7 @@ const TablePage: React.FC = () => {
 
       const endTime = new Date();
       const totalTime = (endTime.getTime() - startTime.getTime()) / 1000;
-      alert(`Withdrawal of ${withdrawAmount} was successful. Time taken: ${totalTime} seconds.`);
+      alert(`Withdrawal of ${withdrawAmount}${devMode ? ' with a dev-mode receipt' : ''} was successful. Time taken: ${totalTime} seconds.`);
       localStorage.setItem('ongoingTransaction', 'false');
     } catch (e) {
       console.error(e);
@@ -80,6 +111,102 @@ const TablePage: React.FC = () => {
     }
   };
 
+  const readReceipt = async (): Promise<number[] | null> => {
+    if (!receiptFile) {
+      alert("Please select a receipt file.");
+      return null;
+    }
+    return Array.from(new Uint8Array(await receiptFile.arrayBuffer()));
+  };
+
+  const handleVerifyReceipt = async () => {
+    const bundle = await readReceipt();
+    if (bundle === null) {
+      return;
+    }
+    try {
+      const info: BundleInfo = await invoke('verify_receipt_bundle', { bundle });
+      const lines = [
+        `Contract: ${info.contract}`,
+        `Created: ${new Date(info.created_at * 1000).toLocaleString()}`,
+        `Image id: ${info.image_id}`,
+        `Receipt: ${info.kind}, ${info.receipt_bytes} bytes`,
+        `Root: ${info.root}`,
+        ...info.nullifiers.map((nullifier) => `Nullifier: ${nullifier}`),
+        ...(info.call ? [`Call: ${info.call}`] : []),
+        ...info.outputs.map(([recipient, count]) => `Output: ${recipient} x${count}`),
+      ];
+      alert(`Receipt is valid.\n\n${lines.join('\n')}`);
+    } catch (e) {
+      console.error(e);
+      alert(`Invalid receipt: ${e}`);
+    }
+  };
+
+  const handleSubmitReceipt = async () => {
+    const bundle = await readReceipt();
+    if (bundle === null) {
+      return;
+    }
+    setIsSubmitting(true);
+    localStorage.setItem('ongoingTransaction', 'true');
+    try {
+      await invoke('submit_receipt_bundle', { addr: userId, bundle });
+      alert("Receipt was submitted.");
+    } catch (e) {
+      console.error(e);
+      alert(`Failed to submit the receipt: ${e}`);
+    } finally {
+      localStorage.setItem('ongoingTransaction', 'false');
+      setIsSubmitting(false);
+      setReceiptFile(null);
+    }
+  };
+
+  const handleAttest = async () => {
+    if (!challenge) {
+      alert("Please enter the challenge you were given.");
+      return;
+    }
+    const blocklist = flagged ? flagged.split(',').map((index) => Number(index.trim())) : null;
+    if (blocklist && blocklist.some((index) => !Number.isInteger(index) || index < 0)) {
+      alert("Flagged indices must be a comma-separated list of leaf indices.");
+      return;
+    }
+    setIsSubmitting(true);
+    try {
+      const path: string = await invoke('attest_ownership', { addr: userId, commitment: null, challenge, disclose, blocklist });
+      alert(`Ownership ${devMode ? 'dev-mode ' : ''}attestation is saved to ${path}`);
+    } catch (e) {
+      console.error(e);
+      alert(`Failed to attest ownership: ${e}`);
+    } finally {
+      setIsSubmitting(false);
+    }
+  };
+
+  const handleCheckAttestation = async () => {
+    const bundle = await readReceipt();
+    if (bundle === null) {
+      return;
+    }
+    try {
+      const info: AttestationInfo = await invoke('verify_ownership_attestation', { addr: userId, bundle, challenge: challenge || null });
+      const lines = [
+        `Contract: ${info.contract}`,
+        `Created: ${new Date(info.created_at * 1000).toLocaleString()}`,
+        `Root: ${info.root}`,
+        `Challenge: ${info.challenge}`,
+        ...(info.leaf ? [`Leaf ${info.leaf[0]}: ${info.leaf[1]}`] : []),
+        ...(info.blocklist ? [`Not on blocklist: ${info.blocklist}`] : []),
+      ];
+      alert(`Attestation is valid.\n\n${lines.join('\n')}`);
+    } catch (e) {
+      console.error(e);
+      alert(`Invalid attestation: ${e}`);
+    }
+  };
+
   return (
     <div style={styles.container}>
       <div style={styles.actionsContainer}>
@@ -124,7 +251,52 @@ const TablePage: React.FC = () => {
                 disabled={isSubmitting}
                 style={styles.button}
               >
-                {isSubmitting && operation === 'withdraw' ? `Withdrawing... ${minutes}:${seconds}s` : 'Withdraw'}
+                {isSubmitting && operation === 'withdraw' ? `Withdrawing${devMode ? ' (dev mode)' : ''}... ${minutes}:${seconds}s` : 'Withdraw'}
+              </button>
+            </div>
+            <div style={styles.selectContainer}>
+              <input
+                type="file"
+                onChange={(e) => setReceiptFile(e.target.files ? e.target.files[0] : null)}
+                value={receiptFile ? undefined : ''}
+                disabled={isSubmitting}
+              />
+              <button onClick={handleVerifyReceipt} disabled={isSubmitting} style={styles.button}>
+                Verify
+              </button>
+              <button onClick={handleSubmitReceipt} disabled={isSubmitting} style={styles.button}>
+                Submit
+              </button>
+            </div>
+            <div style={styles.selectContainer}>
+              <input
+                type="text"
+                placeholder="Challenge"
+                value={challenge}
+                onChange={(e) => setChallenge(e.target.value.trim())}
+                disabled={isSubmitting}
+              />
+              <label>
+                <input
+                  type="checkbox"
+                  checked={disclose}
+                  onChange={(e) => setDisclose(e.target.checked)}
+                  disabled={isSubmitting}
+                />
+                Disclose leaf
+              </label>
+              <input
+                type="text"
+                placeholder="Flagged indices"
+                value={flagged}
+                onChange={(e) => setFlagged(e.target.value)}
+                disabled={isSubmitting}
+              />
+              <button onClick={handleAttest} disabled={isSubmitting} style={styles.button}>
+                Attest
+              </button>
+              <button onClick={handleCheckAttestation} disabled={isSubmitting} style={styles.button}>
+                Check
               </button>
             </div>
           </div>
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mixer_protocol::BatchOutputs;
+
+// The decoder must never panic, and anything it accepts must be canonical.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(outputs) = BatchOutputs::from_journal(data) {
+        assert_eq!(outputs.to_journal(), data);
+    }
+});
@@ -0,0 +1,11 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use mixer_protocol::ReceiptBundle;
+
+// The decoder must never panic, and anything it accepts must be canonical.
+fuzz_target!(|data: &[u8]| {
+    if let Ok(bundle) = ReceiptBundle::from_bytes(data) {
+        assert_eq!(bundle.to_bytes(), data);
+    }
+});
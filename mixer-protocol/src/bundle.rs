@@ -0,0 +1,181 @@
+//! Receipt bundles: a proved withdrawal saved to a file so it can be verified
+//! offline and submitted later. Ownership attestations use the same file, with
+//! the ownership guest's image id and journal.
+//!
+//! | field              | bytes                                         |
+//! |--------------------|-----------------------------------------------|
+//! | magic              | `b"MXRB"`                                     |
+//! | version            | [`BUNDLE_VERSION`]                            |
+//! | image id           | 8 little-endian `u32`                         |
+//! | receipt kind       | [`ReceiptKind`] as a byte, since version 2    |
+//! | contract           | 32, the program the receipt is meant for      |
+//! | created at         | `u64`, unix seconds                           |
+//! | journal            | `u32` length, then the journal the guest committed |
+//! | receipt            | `u32` length, then the postcard `(image id, receipt)` pair `Withdraw` takes |
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use gprimitives::ActorId;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+
+use crate::{journal::Reader, AggregateOutputs, JournalError, PublicOutputs};
+
+pub const BUNDLE_VERSION: u8 = 2;
+
+const MAGIC: &[u8; 4] = b"MXRB";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BundleError {
+    NotABundle,
+    UnsupportedVersion(u8),
+    UnknownReceiptKind,
+    Malformed(JournalError),
+}
+
+impl fmt::Display for BundleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotABundle => write!(f, "not a receipt bundle"),
+            Self::UnsupportedVersion(v) => write!(f, "unsupported bundle version {v}"),
+            Self::UnknownReceiptKind => write!(f, "unknown receipt kind"),
+            Self::Malformed(e) => write!(f, "malformed bundle: {e}"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for BundleError {}
+
+impl From<JournalError> for BundleError {
+    fn from(e: JournalError) -> Self {
+        Self::Malformed(e)
+    }
+}
+
+/// How a receipt was proved. The variants follow risc0's `InnerReceipt` in
+/// order, so the kind can be read off the postcard `(image id, receipt)` pair
+/// without decoding the receipt.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum ReceiptKind {
+    /// A proof per segment; grows with the guest's cycle count.
+    Composite,
+    /// Segments compressed by recursion into one STARK of constant size.
+    Succinct,
+    /// A succinct receipt wrapped in a Groth16 SNARK of a few hundred bytes.
+    Groth16,
+    /// Dev-mode receipt without a proof.
+    Fake,
+}
+
+impl ReceiptKind {
+    pub fn from_u8(tag: u8) -> Option<Self> {
+        match tag {
+            0 => Some(Self::Composite),
+            1 => Some(Self::Succinct),
+            2 => Some(Self::Groth16),
+            3 => Some(Self::Fake),
+            _ => None,
+        }
+    }
+
+    /// Kind of the receipt in a postcard `(image id, receipt)` pair: the
+    /// variant index that follows the eight varint words of the image id.
+    pub fn of(image_id_receipt: &[u8]) -> Option<Self> {
+        let mut bytes = image_id_receipt.iter();
+        let mut varint = || {
+            let mut value = 0u32;
+            for shift in (0..32).step_by(7) {
+                let byte = *bytes.next()?;
+                value |= ((byte & 0x7f) as u32) << shift;
+                if byte & 0x80 == 0 {
+                    return Some(value);
+                }
+            }
+            None
+        };
+        for _ in 0..8 {
+            varint()?;
+        }
+        Self::from_u8(varint()?.try_into().ok()?)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReceiptBundle {
+    pub image_id: [u32; 8],
+    pub kind: ReceiptKind,
+    pub contract: ActorId,
+    pub created_at: u64,
+    pub journal: Vec<u8>,
+    pub image_id_receipt: Vec<u8>,
+}
+
+impl ReceiptBundle {
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(4 + 1 + 32 + 1 + 32 + 8 + 4 + self.journal.len() + 4 + self.image_id_receipt.len());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(BUNDLE_VERSION);
+        self.image_id
+            .iter()
+            .for_each(|word| bytes.extend_from_slice(&word.to_le_bytes()));
+        bytes.push(self.kind as u8);
+        bytes.extend_from_slice(&<[u8; 32]>::from(self.contract));
+        bytes.extend_from_slice(&self.created_at.to_le_bytes());
+        for field in [&self.journal, &self.image_id_receipt] {
+            bytes.extend_from_slice(&(field.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(field);
+        }
+        bytes
+    }
+
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, BundleError> {
+        let mut reader = Reader(bytes);
+        if reader.take(4).ok() != Some(&MAGIC[..]) {
+            return Err(BundleError::NotABundle);
+        }
+        let version = reader.u8()?;
+        if !(1..=BUNDLE_VERSION).contains(&version) {
+            return Err(BundleError::UnsupportedVersion(version));
+        }
+        let mut image_id = [0; 8];
+        for word in image_id.iter_mut() {
+            *word = reader.u32()?;
+        }
+        // Version 1 didn't record the kind, but the receipt does.
+        let kind = match version {
+            1 => None,
+            _ => Some(ReceiptKind::from_u8(reader.u8()?).ok_or(BundleError::UnknownReceiptKind)?),
+        };
+        let contract = ActorId::from(reader.hash()?);
+        let created_at = reader.u64()?;
+        let len = reader.len(1)?;
+        let journal = reader.take(len)?.to_vec();
+        let len = reader.len(1)?;
+        let image_id_receipt = reader.take(len)?.to_vec();
+        reader.finish()?;
+        let kind = match kind {
+            Some(kind) => kind,
+            None => ReceiptKind::of(&image_id_receipt).ok_or(BundleError::UnknownReceiptKind)?,
+        };
+
+        Ok(Self {
+            image_id,
+            kind,
+            contract,
+            created_at,
+            journal,
+            image_id_receipt,
+        })
+    }
+
+    /// The withdrawal the journal commits to, whether it was proved by the
+    /// withdraw guest or aggregated.
+    pub fn outputs(&self) -> Result<PublicOutputs, JournalError> {
+        match PublicOutputs::from_journal(&self.journal) {
+            Err(JournalError::WrongKind(_)) => Ok(AggregateOutputs::from_journal(&self.journal)?.withdraw),
+            outputs => outputs,
+        }
+    }
+}
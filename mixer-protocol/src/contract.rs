@@ -0,0 +1,179 @@
+use alloc::vec::Vec;
+use gprimitives::ActorId;
+use parity_scale_codec::{Decode, Encode};
+use scale_info::TypeInfo;
+
+use crate::{MembershipTree, ReceiptKind};
+
+pub const VARA_UNIT: u128 = 1_000_000_000_000;
+pub const VARA_DEPOSIT_AMOUNT: u32 = 10;
+/// Value locked by a single commitment.
+pub const DEPOSIT_AMOUNT: u128 = VARA_UNIT * VARA_DEPOSIT_AMOUNT as u128;
+
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum ContractHandleAction {
+    Deposit { hashes: Vec<[u8; 32]> },
+    Withdraw { image_id_receipt: Vec<u8> },
+    Enqueue { hashes: Vec<[u8; 32]> },
+    InsertBatch { image_id_receipt: Vec<u8> },
+    ExportState { section: StateSection, from: u64, limit: u32 },
+    BeginImport { predecessor: ActorId },
+    ImportState { chunk: StateChunk },
+    Handover { successor: ActorId },
+    /// Sent by the predecessor with its whole balance; the counts are what it
+    /// exported, so the successor can tell an incomplete import.
+    AcceptHandover { leaves: u64, nullifiers: u64 },
+    WithdrawAndCall { image_id_receipt: Vec<u8>, call: WithdrawCall },
+    SetReceiptPolicy { policy: ReceiptPolicy },
+    /// Stops deposits, batches and withdrawals so the state can be exported.
+    Freeze,
+}
+
+/// Receipts the program forwards to the verifier builtin. Large composite
+/// receipts make withdrawals expensive, and Groth16 ones need a builtin that
+/// verifies them.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct ReceiptPolicy {
+    /// Largest postcard `(image id, receipt)` pair accepted, in bytes.
+    pub max_receipt_bytes: u32,
+    pub accepted_kinds: Vec<ReceiptKind>,
+}
+
+impl Default for ReceiptPolicy {
+    fn default() -> Self {
+        Self {
+            max_receipt_bytes: 512 * 1024,
+            accepted_kinds: alloc::vec![ReceiptKind::Succinct],
+        }
+    }
+}
+
+/// Call the withdrawn value is delivered with. The proof commits to
+/// `sha256(destination ++ fallback ++ payload)`; `fallback` is refunded if the call fails.
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub struct WithdrawCall {
+    pub destination: ActorId,
+    pub fallback: ActorId,
+    pub payload: Vec<u8>,
+}
+
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum ContractHandleEvent {
+    Deposited,
+    WrongDeposit,
+    Withdrawed,
+    Enqueued,
+    BatchInserted,
+    StateExported { chunk: StateChunk, total: u64 },
+    ImportStarted,
+    StateImported,
+    HandedOver,
+    HandoverAccepted,
+    WithdrawnAndCalled { success: bool },
+    WithdrawnSplit,
+    ReceiptPolicySet,
+    Frozen,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum StateSection {
+    Leaves,
+    Roots,
+    Nullifiers,
+    FixedRoots,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum StateChunk {
+    Leaves(Vec<[u8; 32]>),
+    /// Roots with the leaf count each was recorded at.
+    Roots(Vec<([u8; 32], u64)>),
+    Nullifiers(Vec<[u8; 32]>),
+    FixedRoots(Vec<[u8; 32]>),
+}
+
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum StatePayload {
+    Root,
+    Leaves,
+    LeavesLen,
+    Withdrawn{from: u64},
+    WithdrawnAll,
+    HistoryOneFrom{user: ActorId, from: u64},
+    HistoryFrom(Vec<(ActorId, u64)>),
+    HistoryAll,
+    IsSpent { nullifiers: Vec<[u8; 32]> },
+    LeafIndexOf { commitments: Vec<[u8; 32]> },
+    Queue,
+    Frontier,
+    Migration,
+    LeavesPage { from: u64, limit: u32 },
+    FixedTree,
+    /// Whether the program recorded `root` for `tree`, as withdrawals need.
+    KnownRoot { root: [u8; 32], tree: MembershipTree },
+}
+
+#[derive(Encode, Decode, TypeInfo, Clone, Debug, PartialEq, Eq)]
+pub struct TransactionInfo {
+    pub amount: i32,
+    pub time: u64,
+}
+
+#[derive(Debug, PartialEq, Eq, Encode, Decode, TypeInfo)]
+pub enum StateOutput {
+    Root{
+        res: [u8; 32],
+    },
+    Leaves {
+        res: Vec<[u8; 32]>,
+    },
+    LeavesLen{
+        res: u64,
+    },
+    Withdrawn {
+        res: Vec<[u8; 32]>,
+    },
+    WithdrawnAll{
+        res: Vec<[u8; 32]>,
+    },
+    HistoryOneFrom{
+        res: Vec<TransactionInfo>,
+    },
+    HistoryFrom {
+        res: Vec<(ActorId, Vec<TransactionInfo>)>,
+    },
+    HistoryAll {
+        res: Vec<(ActorId, Vec<TransactionInfo>)>,
+    },
+    /// Bit `i % 8` of byte `i / 8` is set if the `i`-th requested nullifier is spent.
+    IsSpent {
+        res: Vec<u8>,
+    },
+    LeafIndexOf {
+        res: Vec<Option<u64>>,
+    },
+    Queue {
+        res: Vec<[u8; 32]>,
+    },
+    Frontier {
+        peaks: Vec<[u8; 32]>,
+        leaves_len: u64,
+    },
+    Migration {
+        admin: ActorId,
+        frozen: bool,
+        importing_from: Option<ActorId>,
+    },
+    LeavesPage {
+        res: Vec<[u8; 32]>,
+    },
+    /// Fields of the contract's `FixedTree`, which batch proofs start from.
+    FixedTree {
+        filled: Vec<[u8; 32]>,
+        root: [u8; 32],
+        leaves_len: u64,
+    },
+    KnownRoot {
+        res: bool,
+    },
+}
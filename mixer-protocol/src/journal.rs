@@ -0,0 +1,336 @@
+//! Byte-packed journal layout committed by the guests with `env::commit_slice`.
+//!
+//! Every journal starts with [`JOURNAL_VERSION`] and a kind byte. Integers are
+//! little-endian, sequences are prefixed with a `u32` length and options with
+//! a `0`/`1` tag, like the tree a root belongs to.
+
+use alloc::vec::Vec;
+use core::fmt;
+
+use crate::{
+    AggregateOutputs, BatchOutputs, BlocklistCommitment, DisclosedLeaf, MembershipTree, OwnershipOutputs, PublicOutputs,
+    WithdrawOutput,
+};
+
+/// Version 2 commits the [`MembershipTree`] after the root of withdraw and ownership journals,
+/// and the fixed tree's update at the end of batch journals.
+pub const JOURNAL_VERSION: u8 = 2;
+
+const WITHDRAW_KIND: u8 = 0;
+const BATCH_KIND: u8 = 1;
+const AGGREGATE_KIND: u8 = 2;
+const OWNERSHIP_KIND: u8 = 3;
+/// Ownership with a trailing blocklist commitment, so plain ownership journals keep their layout.
+const EXCLUSION_KIND: u8 = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalError {
+    UnsupportedVersion(u8),
+    WrongKind(u8),
+    UnexpectedEnd,
+    InvalidTag(u8),
+    TrailingBytes(usize),
+}
+
+impl fmt::Display for JournalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedVersion(v) => write!(f, "unsupported journal version {v}"),
+            Self::WrongKind(k) => write!(f, "unexpected journal kind {k}"),
+            Self::UnexpectedEnd => write!(f, "journal ended unexpectedly"),
+            Self::InvalidTag(t) => write!(f, "invalid tag {t}"),
+            Self::TrailingBytes(n) => write!(f, "{n} trailing bytes after journal"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for JournalError {}
+
+pub(crate) struct Reader<'a>(pub(crate) &'a [u8]);
+
+impl<'a> Reader<'a> {
+    fn header(bytes: &'a [u8], kind: u8) -> Result<Self, JournalError> {
+        let mut reader = Self(bytes);
+        match reader.u8()? {
+            JOURNAL_VERSION => {}
+            version => return Err(JournalError::UnsupportedVersion(version)),
+        }
+        match reader.u8()? {
+            k if k == kind => Ok(reader),
+            k => Err(JournalError::WrongKind(k)),
+        }
+    }
+
+    pub(crate) fn take(&mut self, len: usize) -> Result<&'a [u8], JournalError> {
+        if self.0.len() < len {
+            return Err(JournalError::UnexpectedEnd);
+        }
+        let (head, tail) = self.0.split_at(len);
+        self.0 = tail;
+        Ok(head)
+    }
+
+    pub(crate) fn u8(&mut self) -> Result<u8, JournalError> {
+        Ok(self.take(1)?[0])
+    }
+
+    pub(crate) fn u32(&mut self) -> Result<u32, JournalError> {
+        Ok(u32::from_le_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn u64(&mut self) -> Result<u64, JournalError> {
+        Ok(u64::from_le_bytes(self.take(8)?.try_into().unwrap()))
+    }
+
+    pub(crate) fn hash(&mut self) -> Result<[u8; 32], JournalError> {
+        Ok(self.take(32)?.try_into().unwrap())
+    }
+
+    /// Reads a length prefix, rejecting it early if `item_len`-sized items can't fit.
+    pub(crate) fn len(&mut self, item_len: usize) -> Result<usize, JournalError> {
+        let len = self.u32()? as usize;
+        match len.checked_mul(item_len) {
+            Some(bytes) if bytes <= self.0.len() => Ok(len),
+            _ => Err(JournalError::UnexpectedEnd),
+        }
+    }
+
+    fn tree(&mut self) -> Result<MembershipTree, JournalError> {
+        match self.u8()? {
+            0 => Ok(MembershipTree::AppendOnly { leaves: self.u64()? }),
+            1 => Ok(MembershipTree::Fixed),
+            tag => Err(JournalError::InvalidTag(tag)),
+        }
+    }
+
+    fn hashes(&mut self) -> Result<Vec<[u8; 32]>, JournalError> {
+        let len = self.len(32)?;
+        (0..len).map(|_| self.hash()).collect()
+    }
+
+    pub(crate) fn finish(self) -> Result<(), JournalError> {
+        match self.0.len() {
+            0 => Ok(()),
+            n => Err(JournalError::TrailingBytes(n)),
+        }
+    }
+}
+
+fn header(kind: u8, capacity: usize) -> Vec<u8> {
+    let mut bytes = Vec::with_capacity(2 + capacity);
+    bytes.extend_from_slice(&[JOURNAL_VERSION, kind]);
+    bytes
+}
+
+/// A `0` tag and the leaf count for the append-only tree, a `1` for the fixed one.
+fn put_tree(bytes: &mut Vec<u8>, tree: &MembershipTree) {
+    match tree {
+        MembershipTree::AppendOnly { leaves } => {
+            bytes.push(0);
+            bytes.extend_from_slice(&leaves.to_le_bytes());
+        }
+        MembershipTree::Fixed => bytes.push(1),
+    }
+}
+
+fn put_hashes(bytes: &mut Vec<u8>, hashes: &[[u8; 32]]) {
+    bytes.extend_from_slice(&(hashes.len() as u32).to_le_bytes());
+    hashes.iter().for_each(|hash| bytes.extend_from_slice(hash));
+}
+
+impl PublicOutputs {
+    pub fn to_journal(&self) -> Vec<u8> {
+        let mut bytes = header(WITHDRAW_KIND, self.journal_len());
+        self.put(&mut bytes);
+        bytes
+    }
+
+    pub fn from_journal(bytes: &[u8]) -> Result<Self, JournalError> {
+        let mut reader = Reader::header(bytes, WITHDRAW_KIND)?;
+        let public_outputs = Self::read(&mut reader)?;
+        reader.finish()?;
+
+        Ok(public_outputs)
+    }
+
+    fn journal_len(&self) -> usize {
+        32 + 9 + 4 + 32 * self.used.len() + 33 + 4 + 36 * self.outputs.len()
+    }
+
+    fn put(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.root);
+        put_tree(bytes, &self.tree);
+        put_hashes(bytes, &self.used);
+        match &self.call {
+            Some(call) => {
+                bytes.push(1);
+                bytes.extend_from_slice(call);
+            }
+            None => bytes.push(0),
+        }
+        bytes.extend_from_slice(&(self.outputs.len() as u32).to_le_bytes());
+        for output in self.outputs.iter() {
+            bytes.extend_from_slice(&output.recipient);
+            bytes.extend_from_slice(&output.note_count.to_le_bytes());
+        }
+    }
+
+    fn read(reader: &mut Reader) -> Result<Self, JournalError> {
+        let root = reader.hash()?;
+        let tree = reader.tree()?;
+        let used = reader.hashes()?;
+        let call = match reader.u8()? {
+            0 => None,
+            1 => Some(reader.hash()?),
+            tag => return Err(JournalError::InvalidTag(tag)),
+        };
+        let outputs_len = reader.len(36)?;
+        let outputs = (0..outputs_len)
+            .map(|_| {
+                Ok(WithdrawOutput {
+                    recipient: reader.hash()?,
+                    note_count: reader.u32()?,
+                })
+            })
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            root,
+            tree,
+            used,
+            call,
+            outputs,
+        })
+    }
+}
+
+impl AggregateOutputs {
+    pub fn to_journal(&self) -> Vec<u8> {
+        let mut bytes = header(AGGREGATE_KIND, 32 + self.withdraw.journal_len());
+        self.note_image_id
+            .iter()
+            .for_each(|word| bytes.extend_from_slice(&word.to_le_bytes()));
+        self.withdraw.put(&mut bytes);
+        bytes
+    }
+
+    pub fn from_journal(bytes: &[u8]) -> Result<Self, JournalError> {
+        let mut reader = Reader::header(bytes, AGGREGATE_KIND)?;
+        let mut note_image_id = [0; 8];
+        for word in note_image_id.iter_mut() {
+            *word = reader.u32()?;
+        }
+        let withdraw = PublicOutputs::read(&mut reader)?;
+        reader.finish()?;
+
+        Ok(Self {
+            note_image_id,
+            withdraw,
+        })
+    }
+}
+
+impl BatchOutputs {
+    pub fn to_journal(&self) -> Vec<u8> {
+        let peaks = 4 + 32 * self.new_peaks.len();
+        let filled = 4 + 32 * self.new_filled.len();
+        let mut bytes = header(BATCH_KIND, 32 + 8 + 8 + 32 + 32 + peaks + 32 + 32 + filled);
+        bytes.extend_from_slice(&self.old_root);
+        bytes.extend_from_slice(&self.old_len.to_le_bytes());
+        bytes.extend_from_slice(&self.count.to_le_bytes());
+        bytes.extend_from_slice(&self.batch_hash);
+        bytes.extend_from_slice(&self.new_root);
+        put_hashes(&mut bytes, &self.new_peaks);
+        bytes.extend_from_slice(&self.old_fixed);
+        bytes.extend_from_slice(&self.new_fixed_root);
+        put_hashes(&mut bytes, &self.new_filled);
+        bytes
+    }
+
+    pub fn from_journal(bytes: &[u8]) -> Result<Self, JournalError> {
+        let mut reader = Reader::header(bytes, BATCH_KIND)?;
+        let batch_outputs = Self {
+            old_root: reader.hash()?,
+            old_len: reader.u64()?,
+            count: reader.u64()?,
+            batch_hash: reader.hash()?,
+            new_root: reader.hash()?,
+            new_peaks: reader.hashes()?,
+            old_fixed: reader.hash()?,
+            new_fixed_root: reader.hash()?,
+            new_filled: reader.hashes()?,
+        };
+        reader.finish()?;
+
+        Ok(batch_outputs)
+    }
+}
+
+impl OwnershipOutputs {
+    pub fn to_journal(&self) -> Vec<u8> {
+        let kind = match self.blocklist {
+            Some(_) => EXCLUSION_KIND,
+            None => OWNERSHIP_KIND,
+        };
+        let mut bytes = header(kind, 32 + 9 + 32 + 1 + 8 + 32 + 1 + 32);
+        bytes.extend_from_slice(&self.root);
+        put_tree(&mut bytes, &self.tree);
+        bytes.extend_from_slice(&self.challenge);
+        match &self.leaf {
+            Some(leaf) => {
+                bytes.push(1);
+                bytes.extend_from_slice(&leaf.index.to_le_bytes());
+                bytes.extend_from_slice(&leaf.commitment);
+            }
+            None => bytes.push(0),
+        }
+        match &self.blocklist {
+            Some(BlocklistCommitment::Sorted(hash)) => {
+                bytes.push(0);
+                bytes.extend_from_slice(hash);
+            }
+            Some(BlocklistCommitment::Sparse(root)) => {
+                bytes.push(1);
+                bytes.extend_from_slice(root);
+            }
+            None => {}
+        }
+        bytes
+    }
+
+    pub fn from_journal(bytes: &[u8]) -> Result<Self, JournalError> {
+        let (mut reader, excluding) = match Reader::header(bytes, OWNERSHIP_KIND) {
+            Err(JournalError::WrongKind(EXCLUSION_KIND)) => (Reader::header(bytes, EXCLUSION_KIND)?, true),
+            reader => (reader?, false),
+        };
+        let root = reader.hash()?;
+        let tree = reader.tree()?;
+        let challenge = reader.hash()?;
+        let leaf = match reader.u8()? {
+            0 => None,
+            1 => Some(DisclosedLeaf {
+                index: reader.u64()?,
+                commitment: reader.hash()?,
+            }),
+            tag => return Err(JournalError::InvalidTag(tag)),
+        };
+        let blocklist = match excluding {
+            true => Some(match reader.u8()? {
+                0 => BlocklistCommitment::Sorted(reader.hash()?),
+                1 => BlocklistCommitment::Sparse(reader.hash()?),
+                tag => return Err(JournalError::InvalidTag(tag)),
+            }),
+            false => None,
+        };
+        reader.finish()?;
+
+        Ok(Self {
+            root,
+            tree,
+            challenge,
+            leaf,
+            blocklist,
+        })
+    }
+}
@@ -0,0 +1,31 @@
+//! Types shared by the `Mixer` contract, the wallet and the RISC Zero prover.
+//!
+//! `contract` holds the SCALE-encoded messages exchanged with the program,
+//! `zk` holds the serde-encoded guest inputs, `journal` the byte layout
+//! of what the guests commit, `bundle` the file receipts are saved in and
+//! `merkle` the tree hashing all of them share. With the `risc0` feature,
+//! `MerkleHasher` instantiates that hashing with risc0's SHA-256.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+mod bundle;
+mod contract;
+mod journal;
+mod merkle;
+#[cfg(feature = "risc0")]
+mod risc0;
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors;
+mod zk;
+
+pub use bundle::{BundleError, ReceiptBundle, ReceiptKind, BUNDLE_VERSION};
+pub use contract::*;
+pub use gprimitives::ActorId;
+pub use journal::{JournalError, JOURNAL_VERSION};
+pub use merkle::*;
+#[cfg(feature = "risc0")]
+pub use risc0::{DigestWrapper, MerkleHasher};
+pub use rs_merkle;
+pub use zk::*;
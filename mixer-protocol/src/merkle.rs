@@ -0,0 +1,210 @@
+use alloc::{collections::BTreeMap, vec::Vec};
+use core::marker::PhantomData;
+use rs_merkle::Hasher;
+use serde::{Deserialize, Serialize};
+
+use crate::WithdrawInputError;
+
+pub const LEAF_TAG: u8 = 0;
+pub const NODE_TAG: u8 = 1;
+
+/// Plain SHA-256 as provided by each component: the risc0 implementation in
+/// the contract and the guests, `sp_core` in the wallet.
+pub trait Sha256Fn: Clone {
+    fn sha256(data: &[u8]) -> [u8; 32];
+}
+
+/// Merkle hasher shared by every component. Leaves are `sha256(LEAF_TAG ++ preimage)`
+/// and internal nodes `sha256(NODE_TAG ++ left ++ right)`, so a note preimage can
+/// never be passed off as a pair of children. A lone right-most node is promoted
+/// unchanged, as in `rs_merkle`.
+#[derive(Clone)]
+pub struct TaggedHasher<S>(PhantomData<S>);
+
+impl<S: Sha256Fn> Hasher for TaggedHasher<S> {
+    type Hash = [u8; 32];
+
+    fn hash(data: &[u8]) -> Self::Hash {
+        tagged::<S>(LEAF_TAG, &[data])
+    }
+
+    fn concat_and_hash(left: &Self::Hash, right: Option<&Self::Hash>) -> Self::Hash {
+        match right {
+            Some(right) => tagged::<S>(NODE_TAG, &[left, right]),
+            None => *left,
+        }
+    }
+}
+
+fn tagged<S: Sha256Fn>(tag: u8, parts: &[&[u8]]) -> [u8; 32] {
+    let mut data = alloc::vec![tag];
+    parts.iter().for_each(|part| data.extend_from_slice(part));
+    S::sha256(&data)
+}
+
+/// Depth of the [`FixedTree`]; every withdraw path has exactly this many siblings.
+pub const TREE_DEPTH: usize = 20;
+
+/// Roots of empty subtrees: `[0; 32]` for an empty leaf, then one per level up to the root.
+pub fn zero_hashes<H: Hasher<Hash = [u8; 32]>>() -> Vec<[u8; 32]> {
+    let mut zeros = alloc::vec![[0; 32]];
+    for level in 0..TREE_DEPTH {
+        zeros.push(H::concat_and_hash(&zeros[level], Some(&zeros[level])));
+    }
+    zeros
+}
+
+/// Incremental tree of depth [`TREE_DEPTH`] padded with empty leaves. `filled[level]`
+/// is the last left node written on that level, which is all an append needs.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+pub struct FixedTree {
+    pub filled: Vec<[u8; 32]>,
+    pub root: [u8; 32],
+    pub len: u64,
+}
+
+impl FixedTree {
+    pub const CAPACITY: u64 = 1 << TREE_DEPTH;
+
+    /// Appends `leaf`; `zeros` is [`zero_hashes`] for `H`, computed once by the
+    /// caller instead of on every append.
+    pub fn append<H: Hasher<Hash = [u8; 32]>>(&mut self, zeros: &[[u8; 32]], leaf: [u8; 32]) {
+        assert!(self.len < Self::CAPACITY, "Fixed-depth tree is full");
+        self.filled.resize(TREE_DEPTH, [0; 32]);
+
+        let mut index = self.len;
+        let mut node = leaf;
+        for (level, zero) in zeros.iter().take(TREE_DEPTH).enumerate() {
+            node = if index & 1 == 0 {
+                self.filled[level] = node;
+                H::concat_and_hash(&node, Some(zero))
+            } else {
+                H::concat_and_hash(&self.filled[level], Some(&node))
+            };
+            index >>= 1;
+        }
+        self.root = node;
+        self.len += 1;
+    }
+
+    pub fn root(&self, zeros: &[[u8; 32]]) -> [u8; 32] {
+        match self.len {
+            0 => zeros[TREE_DEPTH],
+            _ => self.root,
+        }
+    }
+
+    /// Leaf hash of the whole state. `filled` doesn't determine the root, so a
+    /// batch proof commits to this to show which tree it appended to.
+    pub fn commitment<H: Hasher<Hash = [u8; 32]>>(&self) -> [u8; 32] {
+        let mut bytes = self.filled.concat();
+        bytes.extend_from_slice(&self.root);
+        bytes.extend_from_slice(&self.len.to_le_bytes());
+        H::hash(&bytes)
+    }
+}
+
+/// Authentication path of the leaf at `index` in the [`FixedTree`], bottom sibling first.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath {
+    pub index: u64,
+    pub siblings: Vec<[u8; 32]>,
+}
+
+impl MerklePath {
+    /// Builds the path from the full list of leaves, as the wallet holds them.
+    pub fn build<H: Hasher<Hash = [u8; 32]>>(leaves: &[[u8; 32]], index: u64) -> Self {
+        let zeros = zero_hashes::<H>();
+        let mut level = leaves.to_vec();
+        let mut position = index as usize;
+        let mut siblings = Vec::with_capacity(TREE_DEPTH);
+        for zero in zeros.iter().take(TREE_DEPTH) {
+            siblings.push(level.get(position ^ 1).copied().unwrap_or(*zero));
+            level = level
+                .chunks(2)
+                .map(|pair| H::concat_and_hash(&pair[0], Some(pair.get(1).unwrap_or(zero))))
+                .collect();
+            position /= 2;
+        }
+        Self { index, siblings }
+    }
+
+    pub fn root<H: Hasher<Hash = [u8; 32]>>(&self, leaf: [u8; 32]) -> [u8; 32] {
+        let mut index = self.index;
+        let mut node = leaf;
+        for sibling in self.siblings.iter() {
+            node = if index & 1 == 0 {
+                H::concat_and_hash(&node, Some(sibling))
+            } else {
+                H::concat_and_hash(sibling, Some(&node))
+            };
+            index >>= 1;
+        }
+        node
+    }
+}
+
+/// Value of a flagged slot in a [`SparseBlocklist`]; free slots hold `[0; 32]`.
+pub const FLAGGED_LEAF: [u8; 32] = [0xff; 32];
+
+/// Sparse tree of depth [`TREE_DEPTH`] over leaf indices, with [`FLAGGED_LEAF`]
+/// at every flagged index. A path to an empty slot shows the index isn't flagged.
+/// Only non-empty nodes are kept, so building it costs `TREE_DEPTH` hashes per flag.
+/// Indices at or beyond [`FixedTree::CAPACITY`] have no slot and are rejected.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SparseBlocklist {
+    levels: Vec<BTreeMap<u64, [u8; 32]>>,
+    zeros: Vec<[u8; 32]>,
+}
+
+impl SparseBlocklist {
+    pub fn new<H: Hasher<Hash = [u8; 32]>>(flagged: &[u64]) -> Result<Self, WithdrawInputError> {
+        if let Some(&index) = flagged.iter().find(|&&index| index >= FixedTree::CAPACITY) {
+            return Err(out_of_range(index));
+        }
+        let zeros = zero_hashes::<H>();
+        let mut levels = alloc::vec![flagged.iter().map(|&index| (index, FLAGGED_LEAF)).collect::<BTreeMap<_, _>>()];
+        for zero in zeros.iter().take(TREE_DEPTH) {
+            let level = levels.last().unwrap();
+            let parents = level
+                .keys()
+                .map(|&index| {
+                    let left = level.get(&(index & !1)).unwrap_or(zero);
+                    let right = level.get(&(index | 1)).unwrap_or(zero);
+                    (index / 2, H::concat_and_hash(left, Some(right)))
+                })
+                .collect();
+            levels.push(parents);
+        }
+        Ok(Self { levels, zeros })
+    }
+
+    pub fn root(&self) -> [u8; 32] {
+        self.levels[TREE_DEPTH].get(&0).copied().unwrap_or(self.zeros[TREE_DEPTH])
+    }
+
+    pub fn contains(&self, index: u64) -> bool {
+        self.levels[0].contains_key(&index)
+    }
+
+    /// Path from the slot at `index` to the root, bottom sibling first.
+    pub fn path(&self, index: u64) -> Result<MerklePath, WithdrawInputError> {
+        if index >= FixedTree::CAPACITY {
+            return Err(out_of_range(index));
+        }
+        let siblings = (0..TREE_DEPTH)
+            .map(|level| {
+                let sibling = (index >> level) ^ 1;
+                self.levels[level].get(&sibling).copied().unwrap_or(self.zeros[level])
+            })
+            .collect();
+        Ok(MerklePath { index, siblings })
+    }
+}
+
+fn out_of_range(index: u64) -> WithdrawInputError {
+    WithdrawInputError::IndexOutOfRange {
+        index,
+        total_leaves_count: FixedTree::CAPACITY,
+    }
+}
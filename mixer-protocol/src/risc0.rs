@@ -0,0 +1,17 @@
+use risc0_zkvm::sha::{Impl, Sha256};
+
+use crate::{Sha256Fn, TaggedHasher};
+
+/// SHA-256 from `risc0_zkvm`: accelerated inside the guests, plain Rust
+/// everywhere else, so the contract, the prover and the wallet hash alike.
+#[derive(Clone)]
+pub struct DigestWrapper;
+
+impl Sha256Fn for DigestWrapper {
+    fn sha256(data: &[u8]) -> [u8; 32] {
+        (*Impl::hash_bytes(data)).into()
+    }
+}
+
+/// The [`TaggedHasher`] every component builds its trees with.
+pub type MerkleHasher = TaggedHasher<DigestWrapper>;
@@ -0,0 +1,266 @@
+//! Fixed tree every component checks its hasher against, so the contract,
+//! the guests and the wallet provably agree on leaf and root hashes.
+//!
+//! [`FIXTURES`] extends the same notes to trees of [`TREE_SIZES`] leaves, with
+//! serialized multiproofs and journals the withdraw guest committed for them.
+//! It is written by `risc0-prover/host/src/bin/vectors.rs`, which runs the
+//! guest. [`check_fixtures`] reads them back with any hasher; the contract and
+//! the wallet check them through their own deposit and proving paths instead.
+
+use alloc::vec::Vec;
+use rs_merkle::{Hasher, MerkleProof, MerkleTree};
+use serde::{Deserialize, Serialize};
+
+use crate::{zero_hashes, FixedTree, Frontier, MembershipTree, MerklePath, PublicOutputs, RustShit};
+
+pub const NOTES: u8 = 5;
+pub const PROVED_INDICES: [usize; 2] = [1, 3];
+pub const FIRST_LEAF: [u8; 32] = [
+    0x98, 0x66, 0x4e, 0xba, 0x4d, 0x34, 0x00, 0x40, 0x70, 0x85, 0x80, 0x74, 0x96, 0xfb, 0x43, 0x7b,
+    0x4a, 0x92, 0x37, 0x4b, 0x99, 0x75, 0xb4, 0xb0, 0x9a, 0x2c, 0x39, 0xd9, 0x81, 0x70, 0x63, 0xfb,
+];
+pub const ROOT: [u8; 32] = [
+    0x70, 0xe0, 0xed, 0x15, 0xc5, 0xd2, 0xf1, 0x78, 0x31, 0x50, 0x40, 0xd9, 0xbd, 0x00, 0xfe, 0x00,
+    0xbe, 0xc3, 0x66, 0xc8, 0x14, 0xd6, 0x8d, 0x4c, 0x6d, 0xf8, 0x6a, 0x35, 0xed, 0xe1, 0x87, 0x3d,
+];
+
+/// Root of the same leaves in the [`FixedTree`].
+pub const FIXED_ROOT: [u8; 32] = [
+    0x75, 0x00, 0xf0, 0x83, 0x15, 0x86, 0x37, 0xf5, 0xbd, 0x5e, 0xef, 0xdf, 0x34, 0xb4, 0x50, 0x38,
+    0x8c, 0x34, 0x34, 0xc1, 0x87, 0x6e, 0x6b, 0xd6, 0x6f, 0x21, 0x7c, 0xc3, 0x5b, 0x37, 0x51, 0xe1,
+];
+
+pub fn note(i: u8) -> RustShit {
+    RustShit([i; 32], [i + 100; 32])
+}
+
+pub fn notes() -> Vec<RustShit> {
+    (0..NOTES).map(note).collect()
+}
+
+/// Panics unless `H` reproduces the vectors through the tree, the frontier,
+/// a serialized multi-proof, the fixed-depth tree and its paths.
+pub fn check<H: Hasher<Hash = [u8; 32]>>() {
+    let leaves: Vec<[u8; 32]> = notes().iter().map(|note| H::hash(&note.to_preimage())).collect();
+    assert_eq!(leaves[0], FIRST_LEAF, "leaf hash mismatch");
+
+    let tree = MerkleTree::<H>::from_leaves(&leaves);
+    assert_eq!(tree.root(), Some(ROOT), "tree root mismatch");
+
+    let mut frontier = Frontier::default();
+    leaves.iter().for_each(|leaf| frontier.append::<H>(*leaf));
+    assert_eq!(frontier.root::<H>(), ROOT, "frontier root mismatch");
+
+    let proof = MerkleProof::<H>::try_from(tree.proof(&PROVED_INDICES).to_bytes()).expect("proof bytes");
+    let proved: Vec<[u8; 32]> = PROVED_INDICES.iter().map(|&i| leaves[i]).collect();
+    assert_eq!(
+        proof.root(&PROVED_INDICES, &proved, leaves.len()).ok(),
+        Some(ROOT),
+        "proof root mismatch"
+    );
+
+    let zeros = zero_hashes::<H>();
+    let mut fixed = FixedTree::default();
+    leaves.iter().for_each(|leaf| fixed.append::<H>(&zeros, *leaf));
+    assert_eq!(fixed.root(&zeros), FIXED_ROOT, "fixed tree root mismatch");
+    for (index, leaf) in leaves.iter().enumerate() {
+        let path = MerklePath::build::<H>(&leaves, index as u64);
+        assert_eq!(path.root::<H>(*leaf), FIXED_ROOT, "fixed path root mismatch");
+    }
+}
+
+pub const FIXTURES_VERSION: u8 = 1;
+pub const FIXTURES: &str = include_str!("../fixtures/vectors.json");
+pub const TREE_SIZES: [u64; 7] = [1, 2, 3, 5, 8, 13, 32];
+
+/// Withdrawals the guest is run on: tree size, spent leaf indices and whether
+/// fixed-depth paths are used instead of a multiproof.
+pub const WITHDRAWALS: [(u64, &[u64], bool); 5] = [
+    (1, &[0], false),
+    (5, &[1, 3], false),
+    (5, &[1, 3], true),
+    (13, &[2, 7, 12], false),
+    (32, &[0, 31], true),
+];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Fixtures {
+    pub version: u8,
+    pub notes: Vec<NoteFixture>,
+    pub trees: Vec<TreeFixture>,
+    pub withdrawals: Vec<WithdrawFixture>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct NoteFixture {
+    #[serde(with = "hex")]
+    pub nullifier: [u8; 32],
+    #[serde(with = "hex")]
+    pub secret: [u8; 32],
+    #[serde(with = "hex")]
+    pub leaf: [u8; 32],
+}
+
+/// Tree over the first `size` notes.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct TreeFixture {
+    pub size: u64,
+    #[serde(with = "hex")]
+    pub root: [u8; 32],
+    #[serde(with = "hex")]
+    pub fixed_root: [u8; 32],
+    pub proofs: Vec<ProofFixture>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ProofFixture {
+    pub indices: Vec<u64>,
+    #[serde(with = "hex")]
+    pub bytes: Vec<u8>,
+}
+
+/// Journal the withdraw guest committed for one of [`WITHDRAWALS`].
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawFixture {
+    pub size: u64,
+    pub indices: Vec<u64>,
+    pub fixed: bool,
+    #[serde(with = "hex")]
+    pub journal: Vec<u8>,
+}
+
+/// Leaf indices a multiproof is recorded for in a tree of `size` leaves.
+pub fn proof_indices(size: u64) -> Vec<Vec<u64>> {
+    let mut sets = alloc::vec![alloc::vec![0], alloc::vec![size - 1], (0..size).step_by(3).collect()];
+    sets.dedup();
+    sets
+}
+
+impl Fixtures {
+    /// Computes the fixtures with `H`, taking the withdraw journals from `journal`,
+    /// which gets the tree's leaves, the spent indices and the membership mode.
+    pub fn generate<H: Hasher<Hash = [u8; 32]>>(
+        mut journal: impl FnMut(&[[u8; 32]], &[u64], bool) -> Vec<u8>,
+    ) -> Self {
+        let size = TREE_SIZES[TREE_SIZES.len() - 1];
+        let notes: Vec<NoteFixture> = (0..size as u8)
+            .map(note)
+            .map(|note| NoteFixture {
+                nullifier: note.0,
+                secret: note.1,
+                leaf: H::hash(&note.to_preimage()),
+            })
+            .collect();
+        let leaves: Vec<[u8; 32]> = notes.iter().map(|note| note.leaf).collect();
+        let zeros = zero_hashes::<H>();
+
+        let trees = TREE_SIZES
+            .iter()
+            .map(|&size| {
+                let leaves = &leaves[..size as usize];
+                let tree = MerkleTree::<H>::from_leaves(leaves);
+                let proofs = proof_indices(size)
+                    .into_iter()
+                    .map(|indices| {
+                        let proved: Vec<usize> = indices.iter().map(|&i| i as usize).collect();
+                        ProofFixture {
+                            bytes: tree.proof(&proved).to_bytes(),
+                            indices,
+                        }
+                    })
+                    .collect();
+                let mut fixed = FixedTree::default();
+                leaves.iter().for_each(|leaf| fixed.append::<H>(&zeros, *leaf));
+                TreeFixture {
+                    size,
+                    root: tree.root().unwrap_or_default(),
+                    fixed_root: fixed.root(&zeros),
+                    proofs,
+                }
+            })
+            .collect();
+
+        let withdrawals = WITHDRAWALS
+            .iter()
+            .map(|&(size, indices, fixed)| WithdrawFixture {
+                size,
+                indices: indices.to_vec(),
+                fixed,
+                journal: journal(&leaves[..size as usize], indices, fixed),
+            })
+            .collect();
+
+        Self {
+            version: FIXTURES_VERSION,
+            notes,
+            trees,
+            withdrawals,
+        }
+    }
+}
+
+/// Panics unless `H` reproduces the notes' leaves, every tree's root through
+/// a [`Frontier`] and its fixed root through a [`MerklePath`], rather than the
+/// tree builders the fixtures were written with. Every recorded multiproof has
+/// to lead back to its tree's root, and the journals the guest committed have
+/// to decode to those roots and the spent nullifiers.
+pub fn check_fixtures<H: Hasher<Hash = [u8; 32]>>(fixtures: &Fixtures) {
+    assert_eq!(fixtures.version, FIXTURES_VERSION, "fixtures version mismatch");
+    let size = TREE_SIZES[TREE_SIZES.len() - 1];
+    assert_eq!(fixtures.notes.len() as u64, size, "note count mismatch");
+    for (i, fixture) in fixtures.notes.iter().enumerate() {
+        let note = note(i as u8);
+        assert_eq!((fixture.nullifier, fixture.secret), (note.0, note.1), "note {i} mismatch");
+        assert_eq!(fixture.leaf, H::hash(&note.to_preimage()), "leaf {i} mismatch");
+    }
+    let leaves: Vec<[u8; 32]> = fixtures.notes.iter().map(|note| note.leaf).collect();
+
+    let sizes: Vec<u64> = fixtures.trees.iter().map(|tree| tree.size).collect();
+    assert_eq!(sizes, TREE_SIZES, "tree sizes mismatch");
+    for tree in fixtures.trees.iter() {
+        let leaves = &leaves[..tree.size as usize];
+        let mut frontier = Frontier::default();
+        leaves.iter().for_each(|leaf| frontier.append::<H>(*leaf));
+        assert_eq!(frontier.root::<H>(), tree.root, "root of {} leaves mismatch", tree.size);
+        let last = tree.size - 1;
+        let fixed_root = MerklePath::build::<H>(leaves, last).root::<H>(leaves[last as usize]);
+        assert_eq!(fixed_root, tree.fixed_root, "fixed root of {} leaves mismatch", tree.size);
+
+        let indices: Vec<Vec<u64>> = tree.proofs.iter().map(|proof| proof.indices.clone()).collect();
+        assert_eq!(indices, proof_indices(tree.size), "proofs of {} leaves mismatch", tree.size);
+        for proof in tree.proofs.iter() {
+            let indices: Vec<usize> = proof.indices.iter().map(|&i| i as usize).collect();
+            let proved: Vec<[u8; 32]> = indices.iter().map(|&i| leaves[i]).collect();
+            let root = MerkleProof::<H>::try_from(proof.bytes.clone())
+                .ok()
+                .and_then(|proof| proof.root(&indices, &proved, leaves.len()).ok());
+            assert_eq!(root, Some(tree.root), "proof of {:?} in {} leaves mismatch", proof.indices, tree.size);
+        }
+    }
+
+    assert_eq!(fixtures.withdrawals.len(), WITHDRAWALS.len(), "withdrawal count mismatch");
+    for (withdrawal, &(size, indices, fixed)) in fixtures.withdrawals.iter().zip(WITHDRAWALS.iter()) {
+        assert_eq!(
+            (withdrawal.size, &withdrawal.indices[..], withdrawal.fixed),
+            (size, indices, fixed),
+            "withdrawal mismatch"
+        );
+        let tree = fixtures.trees.iter().find(|tree| tree.size == size).expect("withdrawal tree is recorded");
+        let (root, tree) = if fixed {
+            (tree.fixed_root, MembershipTree::Fixed)
+        } else {
+            (tree.root, MembershipTree::AppendOnly { leaves: size })
+        };
+        let expected = PublicOutputs {
+            root,
+            tree,
+            used: indices.iter().map(|&i| note(i as u8).0).collect(),
+            call: None,
+            outputs: Vec::new(),
+        };
+        let outputs = PublicOutputs::from_journal(&withdrawal.journal).expect("journal decodes");
+        assert_eq!(outputs, expected, "journal of {indices:?} in {size} leaves mismatch");
+        // The host writes journals the way the guest does, byte for byte.
+        assert_eq!(outputs.to_journal(), withdrawal.journal, "journal layout of {indices:?} in {size} leaves mismatch");
+    }
+}
@@ -0,0 +1,439 @@
+use alloc::vec::Vec;
+use core::fmt;
+use parity_scale_codec::{Decode, Encode};
+use rs_merkle::Hasher;
+use scale_info::TypeInfo;
+use serde::{Deserialize, Serialize};
+
+use crate::{FixedTree, MerklePath, TREE_DEPTH};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(C)]
+pub struct RustShit(pub [u8; 32], pub [u8; 32]);
+
+impl RustShit {
+    /// Bytes the leaf commitment is hashed from: nullifier followed by secret.
+    pub fn to_preimage(&self) -> [u8; 64] {
+        let mut preimage = [0; 64];
+        preimage[..32].copy_from_slice(&self.0);
+        preimage[32..].copy_from_slice(&self.1);
+        preimage
+    }
+}
+
+impl From<[u8; 64]> for RustShit {
+    fn from(preimage: [u8; 64]) -> Self {
+        let mut nullifier = [0; 32];
+        let mut secret = [0; 32];
+        nullifier.copy_from_slice(&preimage[..32]);
+        secret.copy_from_slice(&preimage[32..]);
+        Self(nullifier, secret)
+    }
+}
+
+pub const WITHDRAW_INPUT_VERSION: u8 = 2;
+
+/// Everything the withdraw guest reads, in a single `env::read()`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct WithdrawInput {
+    pub version: u8,
+    pub membership: Membership,
+    pub notes: Vec<RustShit>,
+    pub call: Option<CallRequest>,
+    pub outputs: Vec<WithdrawOutput>,
+}
+
+/// How `notes` are shown to be in the pool. Leaf positions must be strictly increasing.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Membership {
+    /// `rs_merkle` multiproof against the append-only tree of `total_leaves_count` leaves.
+    MultiProof {
+        proof_bytes: Vec<u8>,
+        indices: Vec<u64>,
+        total_leaves_count: u64,
+    },
+    /// One path per note into the [`FixedTree`](crate::FixedTree); cost and witness
+    /// shape don't depend on the pool size.
+    Paths(Vec<MerklePath>),
+}
+
+/// Tree a membership proof reaches its root in, committed next to the root:
+/// the contract only honours a root for the tree, and the size, it recorded it for.
+#[derive(Serialize, Deserialize, Encode, Decode, TypeInfo, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MembershipTree {
+    /// The append-only tree when it held `leaves` leaves.
+    AppendOnly { leaves: u64 },
+    /// The [`FixedTree`](crate::FixedTree).
+    Fixed,
+}
+
+impl WithdrawInput {
+    pub fn new(proof_bytes: Vec<u8>, indices: Vec<u64>, notes: Vec<RustShit>, total_leaves_count: u64) -> Self {
+        Self::with_membership(
+            Membership::MultiProof {
+                proof_bytes,
+                indices,
+                total_leaves_count,
+            },
+            notes,
+        )
+    }
+
+    pub fn with_paths(paths: Vec<MerklePath>, notes: Vec<RustShit>) -> Self {
+        Self::with_membership(Membership::Paths(paths), notes)
+    }
+
+    fn with_membership(membership: Membership, notes: Vec<RustShit>) -> Self {
+        Self {
+            version: WITHDRAW_INPUT_VERSION,
+            membership,
+            notes,
+            call: None,
+            outputs: Vec::new(),
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), WithdrawInputError> {
+        if self.version != WITHDRAW_INPUT_VERSION {
+            return Err(WithdrawInputError::UnsupportedVersion(self.version));
+        }
+        if self.notes.is_empty() {
+            return Err(WithdrawInputError::NoNotes);
+        }
+        self.membership.validate(self.notes.len())
+    }
+}
+
+impl Membership {
+    pub fn tree(&self) -> MembershipTree {
+        match self {
+            Membership::MultiProof {
+                total_leaves_count, ..
+            } => MembershipTree::AppendOnly {
+                leaves: *total_leaves_count,
+            },
+            Membership::Paths(_) => MembershipTree::Fixed,
+        }
+    }
+
+    /// Positions of the proved leaves, in order.
+    pub fn indices(&self) -> Vec<u64> {
+        match self {
+            Membership::MultiProof { indices, .. } => indices.clone(),
+            Membership::Paths(paths) => paths.iter().map(|path| path.index).collect(),
+        }
+    }
+
+    fn validate(&self, note_count: usize) -> Result<(), WithdrawInputError> {
+        let total_leaves_count = match self {
+            Membership::MultiProof {
+                total_leaves_count, ..
+            } => *total_leaves_count,
+            Membership::Paths(paths) => {
+                if let Some(path) = paths.iter().find(|path| path.siblings.len() != TREE_DEPTH) {
+                    return Err(WithdrawInputError::WrongPathDepth {
+                        index: path.index,
+                        depth: path.siblings.len(),
+                    });
+                }
+                FixedTree::CAPACITY
+            }
+        };
+        let indices = self.indices();
+        if indices.len() != note_count {
+            return Err(WithdrawInputError::LengthMismatch {
+                indices: indices.len(),
+                notes: note_count,
+            });
+        }
+        for pair in indices.windows(2) {
+            if pair[0] == pair[1] {
+                return Err(WithdrawInputError::DuplicateIndex(pair[0]));
+            }
+            if pair[0] > pair[1] {
+                return Err(WithdrawInputError::UnsortedIndices);
+            }
+        }
+        match indices.last() {
+            Some(&index) if index >= total_leaves_count => Err(WithdrawInputError::IndexOutOfRange {
+                index,
+                total_leaves_count,
+            }),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WithdrawInputError {
+    UnsupportedVersion(u8),
+    NoNotes,
+    LengthMismatch { indices: usize, notes: usize },
+    UnsortedIndices,
+    DuplicateIndex(u64),
+    IndexOutOfRange { index: u64, total_leaves_count: u64 },
+    WrongPathDepth { index: u64, depth: usize },
+    BlocklistPathMismatch { index: u64, path_index: u64 },
+}
+
+impl fmt::Display for WithdrawInputError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnsupportedVersion(version) => write!(f, "unsupported withdraw input version {version}"),
+            Self::NoNotes => write!(f, "no notes to withdraw"),
+            Self::LengthMismatch { indices, notes } => {
+                write!(f, "{indices} indices given for {notes} notes")
+            }
+            Self::UnsortedIndices => write!(f, "indices are not sorted"),
+            Self::DuplicateIndex(index) => write!(f, "index {index} is repeated"),
+            Self::IndexOutOfRange { index, total_leaves_count } => {
+                write!(f, "index {index} is out of range for {total_leaves_count} leaves")
+            }
+            Self::WrongPathDepth { index, depth } => {
+                write!(f, "path for index {index} has {depth} siblings instead of {TREE_DEPTH}")
+            }
+            Self::BlocklistPathMismatch { index, path_index } => {
+                write!(f, "blocklist path is for index {path_index} instead of {index}")
+            }
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for WithdrawInputError {}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct PublicOutputs {
+    pub root: [u8; 32],
+    pub tree: MembershipTree,
+    pub used: Vec<[u8; 32]>,
+    pub call: Option<[u8; 32]>,
+    pub outputs: Vec<WithdrawOutput>,
+}
+
+/// Share of a split withdrawal: `note_count` notes are paid out to `recipient`.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WithdrawOutput {
+    pub recipient: [u8; 32],
+    pub note_count: u32,
+}
+
+/// Program call the withdrawn value is delivered with; the guest commits
+/// `sha256(preimage())` so the contract can only forward to this exact call.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CallRequest {
+    pub destination: [u8; 32],
+    pub fallback: [u8; 32],
+    pub payload: Vec<u8>,
+}
+
+impl CallRequest {
+    pub fn preimage(&self) -> Vec<u8> {
+        [&self.destination[..], &self.fallback[..], &self.payload[..]].concat()
+    }
+}
+
+impl From<crate::WithdrawCall> for CallRequest {
+    fn from(call: crate::WithdrawCall) -> Self {
+        Self {
+            destination: call.destination.into(),
+            fallback: call.fallback.into(),
+            payload: call.payload,
+        }
+    }
+}
+
+pub const OWNERSHIP_INPUT_VERSION: u8 = 2;
+
+/// Input of the ownership guest: one note shown to be in the pool without
+/// spending it, bound to a verifier's `challenge`. With `disclose` the leaf
+/// index and commitment are committed too; otherwise only that some deposit
+/// under `root` is owned. With a `blocklist` the note's leaf index is also
+/// shown not to be flagged.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct OwnershipInput {
+    pub version: u8,
+    pub membership: Membership,
+    pub note: RustShit,
+    pub challenge: [u8; 32],
+    pub disclose: bool,
+    pub blocklist: Option<Blocklist>,
+}
+
+impl OwnershipInput {
+    pub fn new(membership: Membership, note: RustShit, challenge: [u8; 32], disclose: bool) -> Self {
+        Self {
+            version: OWNERSHIP_INPUT_VERSION,
+            membership,
+            note,
+            challenge,
+            disclose,
+            blocklist: None,
+        }
+    }
+
+    pub fn excluding(self, blocklist: Blocklist) -> Self {
+        Self {
+            blocklist: Some(blocklist),
+            ..self
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), WithdrawInputError> {
+        if self.version != OWNERSHIP_INPUT_VERSION {
+            return Err(WithdrawInputError::UnsupportedVersion(self.version));
+        }
+        self.membership.validate(1)?;
+        match &self.blocklist {
+            Some(blocklist) => blocklist.validate(self.membership.indices()[0]),
+            None => Ok(()),
+        }
+    }
+}
+
+/// Published leaf indices of flagged deposits.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum Blocklist {
+    /// Every flagged index, strictly increasing.
+    Sorted(Vec<u64>),
+    /// Path to the note's empty slot in the [`SparseBlocklist`](crate::SparseBlocklist) with `root`.
+    Sparse { root: [u8; 32], path: MerklePath },
+}
+
+impl Blocklist {
+    fn validate(&self, index: u64) -> Result<(), WithdrawInputError> {
+        match self {
+            Blocklist::Sorted(flagged) if flagged.windows(2).any(|pair| pair[0] >= pair[1]) => {
+                Err(WithdrawInputError::UnsortedIndices)
+            }
+            Blocklist::Sparse { path, .. } if path.siblings.len() != TREE_DEPTH => {
+                Err(WithdrawInputError::WrongPathDepth {
+                    index: path.index,
+                    depth: path.siblings.len(),
+                })
+            }
+            Blocklist::Sparse { path, .. } if path.index != index => Err(WithdrawInputError::BlocklistPathMismatch {
+                index,
+                path_index: path.index,
+            }),
+            // The sparse tree has no slot for the note, and its path would wrap onto a lower one.
+            Blocklist::Sparse { .. } if index >= FixedTree::CAPACITY => Err(WithdrawInputError::IndexOutOfRange {
+                index,
+                total_leaves_count: FixedTree::CAPACITY,
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    /// Whether `index` is shown not to be flagged.
+    pub fn excludes<H: Hasher<Hash = [u8; 32]>>(&self, index: u64) -> bool {
+        match self {
+            Blocklist::Sorted(flagged) => flagged.binary_search(&index).is_err(),
+            Blocklist::Sparse { root, path } => {
+                index < FixedTree::CAPACITY && path.index == index && path.root::<H>([0; 32]) == *root
+            }
+        }
+    }
+
+    /// What identifies the list in the journal: the leaf hash of the flagged
+    /// indices' little-endian bytes, or the sparse tree's root.
+    pub fn commitment<H: Hasher<Hash = [u8; 32]>>(&self) -> BlocklistCommitment {
+        match self {
+            Blocklist::Sorted(flagged) => {
+                let bytes: Vec<u8> = flagged.iter().flat_map(|index| index.to_le_bytes()).collect();
+                BlocklistCommitment::Sorted(H::hash(&bytes))
+            }
+            Blocklist::Sparse { root, .. } => BlocklistCommitment::Sparse(*root),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BlocklistCommitment {
+    Sorted([u8; 32]),
+    Sparse([u8; 32]),
+}
+
+/// What the ownership guest commits. The nullifier is never revealed, so the
+/// note stays spendable and the attestation can't be linked to its withdrawal.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct OwnershipOutputs {
+    pub root: [u8; 32],
+    pub tree: MembershipTree,
+    pub challenge: [u8; 32],
+    pub leaf: Option<DisclosedLeaf>,
+    /// Blocklist the note was shown to be absent from.
+    pub blocklist: Option<BlocklistCommitment>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DisclosedLeaf {
+    pub index: u64,
+    pub commitment: [u8; 32],
+}
+
+/// Input of the aggregation guest: journals of single-note withdraw receipts,
+/// each proved by `note_image_id` and supplied to the prover as an assumption.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct AggregateInput {
+    pub note_image_id: [u32; 8],
+    pub journals: Vec<Vec<u8>>,
+}
+
+/// What the aggregation guest commits: the withdrawal the verified notes add up
+/// to, plus the image id they were proved with, which the contract pins.
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct AggregateOutputs {
+    pub note_image_id: [u32; 8],
+    pub withdraw: PublicOutputs,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct BatchOutputs {
+    pub old_root: [u8; 32],
+    pub old_len: u64,
+    pub count: u64,
+    pub batch_hash: [u8; 32],
+    pub new_root: [u8; 32],
+    pub new_peaks: Vec<[u8; 32]>,
+    /// [`FixedTree::commitment`] of the fixed tree the batch was appended to.
+    pub old_fixed: [u8; 32],
+    pub new_fixed_root: [u8; 32],
+    pub new_filled: Vec<[u8; 32]>,
+}
+
+/// Right edge of the append-only leaves tree: one peak per set bit of `len`,
+/// largest subtree first. Roots match `rs_merkle::MerkleTree` over the same leaves.
+#[derive(Serialize, Deserialize, Clone, Default, Debug, PartialEq, Eq)]
+pub struct Frontier {
+    pub peaks: Vec<[u8; 32]>,
+    pub len: u64,
+}
+
+impl Frontier {
+    /// Whether there's a peak for each set bit of `len`. A frontier with more
+    /// can hash to a recorded root and still grow into a tree that never held them.
+    pub fn is_well_formed(&self) -> bool {
+        self.peaks.len() == self.len.count_ones() as usize
+    }
+
+    pub fn append<H: Hasher<Hash = [u8; 32]>>(&mut self, leaf: [u8; 32]) {
+        let mut node = leaf;
+        let mut height = 0;
+        while (self.len >> height) & 1 == 1 {
+            let left = self.peaks.pop().expect("Frontier is missing a peak");
+            node = H::concat_and_hash(&left, Some(&node));
+            height += 1;
+        }
+        self.peaks.push(node);
+        self.len += 1;
+    }
+
+    pub fn root<H: Hasher<Hash = [u8; 32]>>(&self) -> [u8; 32] {
+        let mut peaks = self.peaks.iter().rev();
+        match peaks.next() {
+            Some(&last) => peaks.fold(last, |acc, peak| H::concat_and_hash(peak, Some(&acc))),
+            None => Default::default(),
+        }
+    }
+}
@@ -0,0 +1,119 @@
+use mixer_protocol::{
+    zero_hashes, Blocklist, BlocklistCommitment, FixedTree, Membership, MerklePath, OwnershipInput, RustShit,
+    Sha256Fn, SparseBlocklist, TaggedHasher, WithdrawInputError, FLAGGED_LEAF, TREE_DEPTH,
+};
+use sha2::{Digest, Sha256};
+
+#[derive(Clone)]
+struct Sha2;
+
+impl Sha256Fn for Sha2 {
+    fn sha256(data: &[u8]) -> [u8; 32] {
+        Sha256::digest(data).into()
+    }
+}
+
+type H = TaggedHasher<Sha2>;
+
+const FLAGGED: [u64; 4] = [0, 5, 6, 1000];
+
+#[test]
+fn sparse_tree_matches_a_dense_one() {
+    let blocklist = SparseBlocklist::new::<H>(&FLAGGED).unwrap();
+    let mut slots = vec![[0; 32]; 1001];
+    FLAGGED.iter().for_each(|&index| slots[index as usize] = FLAGGED_LEAF);
+    let zeros = zero_hashes::<H>();
+    let mut dense = FixedTree::default();
+    slots.iter().for_each(|slot| dense.append::<H>(&zeros, *slot));
+
+    assert_eq!(blocklist.root(), dense.root(&zeros));
+    for index in [0, 1, 4, 7, 999, 1000, FixedTree::CAPACITY - 1] {
+        let path = blocklist.path(index).unwrap();
+        assert_eq!(path, MerklePath::build::<H>(&slots, index));
+        let slot = if blocklist.contains(index) { FLAGGED_LEAF } else { [0; 32] };
+        assert_eq!(path.root::<H>(slot), blocklist.root());
+    }
+}
+
+#[test]
+fn excludes_only_unflagged_indices() {
+    let tree = SparseBlocklist::new::<H>(&FLAGGED).unwrap();
+    let sorted = Blocklist::Sorted(FLAGGED.to_vec());
+    for index in [1, 4, 7, 1001] {
+        assert!(sorted.excludes::<H>(index));
+        let sparse = Blocklist::Sparse { root: tree.root(), path: tree.path(index).unwrap() };
+        assert!(sparse.excludes::<H>(index));
+    }
+    for index in FLAGGED {
+        assert!(!sorted.excludes::<H>(index));
+        let sparse = Blocklist::Sparse { root: tree.root(), path: tree.path(index).unwrap() };
+        assert!(!sparse.excludes::<H>(index));
+    }
+
+    // A path for another slot doesn't count, even if that slot is empty.
+    let sparse = Blocklist::Sparse { root: tree.root(), path: tree.path(4).unwrap() };
+    assert!(!sparse.excludes::<H>(5));
+}
+
+#[test]
+fn commitments_tell_the_forms_apart() {
+    let tree = SparseBlocklist::new::<H>(&FLAGGED).unwrap();
+    assert_eq!(
+        Blocklist::Sparse { root: tree.root(), path: tree.path(1).unwrap() }.commitment::<H>(),
+        BlocklistCommitment::Sparse(tree.root())
+    );
+    let bytes: Vec<u8> = FLAGGED.iter().flat_map(|index| index.to_le_bytes()).collect();
+    assert_eq!(
+        Blocklist::Sorted(FLAGGED.to_vec()).commitment::<H>(),
+        BlocklistCommitment::Sorted(Sha2::sha256(&[&[0][..], &bytes].concat()))
+    );
+}
+
+#[test]
+fn validates_blocklist_input() {
+    let membership = Membership::MultiProof {
+        proof_bytes: vec![],
+        indices: vec![4],
+        total_leaves_count: 10,
+    };
+    let input = |blocklist| OwnershipInput::new(membership.clone(), RustShit([1; 32], [2; 32]), [3; 32], false).excluding(blocklist);
+    let tree = SparseBlocklist::new::<H>(&FLAGGED).unwrap();
+
+    assert_eq!(input(Blocklist::Sorted(FLAGGED.to_vec())).validate(), Ok(()));
+    assert_eq!(input(Blocklist::Sorted(vec![5, 5])).validate(), Err(WithdrawInputError::UnsortedIndices));
+    assert_eq!(input(Blocklist::Sparse { root: tree.root(), path: tree.path(4).unwrap() }).validate(), Ok(()));
+    assert_eq!(
+        input(Blocklist::Sparse { root: tree.root(), path: tree.path(3).unwrap() }).validate(),
+        Err(WithdrawInputError::BlocklistPathMismatch { index: 4, path_index: 3 })
+    );
+
+    let mut short = tree.path(4).unwrap();
+    short.siblings.pop();
+    assert_eq!(
+        input(Blocklist::Sparse { root: tree.root(), path: short }).validate(),
+        Err(WithdrawInputError::WrongPathDepth { index: 4, depth: TREE_DEPTH - 1 })
+    );
+}
+
+#[test]
+fn rejects_indices_without_a_slot() {
+    let out_of_range = |index| WithdrawInputError::IndexOutOfRange { index, total_leaves_count: FixedTree::CAPACITY };
+    assert_eq!(SparseBlocklist::new::<H>(&[5, FixedTree::CAPACITY]), Err(out_of_range(FixedTree::CAPACITY)));
+
+    // Slot 5 is empty, but a note at `CAPACITY + 5` must not borrow its path.
+    let tree = SparseBlocklist::new::<H>(&FLAGGED).unwrap();
+    let index = FixedTree::CAPACITY + 5;
+    assert_eq!(tree.path(index), Err(out_of_range(index)));
+    let mut wrapped = tree.path(5).unwrap();
+    wrapped.index = index;
+    let sparse = Blocklist::Sparse { root: tree.root(), path: wrapped };
+    assert!(!sparse.excludes::<H>(index));
+
+    let membership = Membership::MultiProof {
+        proof_bytes: vec![],
+        indices: vec![index],
+        total_leaves_count: index + 1,
+    };
+    let input = OwnershipInput::new(membership, RustShit([1; 32], [2; 32]), [3; 32], false).excluding(sparse);
+    assert_eq!(input.validate(), Err(out_of_range(index)));
+}
@@ -0,0 +1,114 @@
+use mixer_protocol::*;
+
+fn bundle(journal: Vec<u8>) -> ReceiptBundle {
+    ReceiptBundle {
+        image_id: [1, 2, 3, 4, 5, 6, 7, 8],
+        kind: ReceiptKind::Succinct,
+        contract: ActorId::from([0x42; 32]),
+        created_at: 1_700_000_000,
+        journal,
+        image_id_receipt: vec![0xab; 100],
+    }
+}
+
+fn withdraw() -> PublicOutputs {
+    PublicOutputs {
+        root: [0x11; 32],
+        tree: MembershipTree::AppendOnly { leaves: 1 },
+        used: vec![[0x22; 32]],
+        call: None,
+        outputs: vec![],
+    }
+}
+
+#[test]
+fn roundtrip() {
+    let bundle = bundle(withdraw().to_journal());
+    let bytes = bundle.to_bytes();
+    assert_eq!(&bytes[..5], b"MXRB\x02");
+    assert_eq!(bytes[5 + 32], ReceiptKind::Succinct as u8);
+    assert_eq!(ReceiptBundle::from_bytes(&bytes), Ok(bundle));
+}
+
+#[test]
+fn receipt_kind_of_envelope() {
+    // Eight varint words of the image id, one of them two bytes long, then the receipt's variant.
+    let envelope = |tag: u8| [&[1, 2, 0x80, 0x01, 4, 5, 6, 7, 8][..], &[tag, 0xaa, 0xbb]].concat();
+    assert_eq!(ReceiptKind::of(&envelope(0)), Some(ReceiptKind::Composite));
+    assert_eq!(ReceiptKind::of(&envelope(1)), Some(ReceiptKind::Succinct));
+    assert_eq!(ReceiptKind::of(&envelope(2)), Some(ReceiptKind::Groth16));
+    assert_eq!(ReceiptKind::of(&envelope(3)), Some(ReceiptKind::Fake));
+    assert_eq!(ReceiptKind::of(&envelope(4)), None);
+    assert_eq!(ReceiptKind::of(&envelope(1)[..8]), None);
+}
+
+#[test]
+fn version_1_takes_the_kind_from_the_receipt() {
+    let mut bundle = bundle(withdraw().to_journal());
+    bundle.image_id_receipt = [&[1, 2, 3, 4, 5, 6, 7, 8, 3][..], &[0xab; 20]].concat();
+    bundle.kind = ReceiptKind::Fake;
+    let mut bytes = bundle.to_bytes();
+    bytes[4] = 1;
+    bytes.remove(5 + 32);
+    assert_eq!(ReceiptBundle::from_bytes(&bytes), Ok(bundle.clone()));
+
+    bundle.image_id_receipt = vec![0xab; 100];
+    let mut bytes = bundle.to_bytes();
+    bytes[4] = 1;
+    bytes.remove(5 + 32);
+    assert_eq!(ReceiptBundle::from_bytes(&bytes), Err(BundleError::UnknownReceiptKind));
+}
+
+#[test]
+fn outputs_of_either_kind() {
+    assert_eq!(bundle(withdraw().to_journal()).outputs(), Ok(withdraw()));
+
+    let aggregate = AggregateOutputs {
+        note_image_id: [9; 8],
+        withdraw: withdraw(),
+    };
+    assert_eq!(bundle(aggregate.to_journal()).outputs(), Ok(withdraw()));
+
+    let batch = BatchOutputs {
+        old_root: [0; 32],
+        old_len: 0,
+        count: 0,
+        batch_hash: [0; 32],
+        new_root: [0; 32],
+        new_peaks: vec![],
+        old_fixed: [0; 32],
+        new_fixed_root: [0; 32],
+        new_filled: vec![],
+    };
+    assert_eq!(bundle(batch.to_journal()).outputs(), Err(JournalError::WrongKind(1)));
+}
+
+#[test]
+fn rejects_malformed() {
+    let bytes = bundle(withdraw().to_journal()).to_bytes();
+
+    assert_eq!(ReceiptBundle::from_bytes(b"MXR"), Err(BundleError::NotABundle));
+    assert_eq!(ReceiptBundle::from_bytes(&bytes[1..]), Err(BundleError::NotABundle));
+
+    let mut newer = bytes.clone();
+    newer[4] = BUNDLE_VERSION + 1;
+    assert_eq!(ReceiptBundle::from_bytes(&newer), Err(BundleError::UnsupportedVersion(BUNDLE_VERSION + 1)));
+
+    let mut unknown = bytes.clone();
+    unknown[5 + 32] = 9;
+    assert_eq!(ReceiptBundle::from_bytes(&unknown), Err(BundleError::UnknownReceiptKind));
+
+    for len in 5..bytes.len() {
+        assert_eq!(
+            ReceiptBundle::from_bytes(&bytes[..len]),
+            Err(BundleError::Malformed(JournalError::UnexpectedEnd)),
+        );
+    }
+
+    let mut trailing = bytes;
+    trailing.push(0);
+    assert_eq!(
+        ReceiptBundle::from_bytes(&trailing),
+        Err(BundleError::Malformed(JournalError::TrailingBytes(1))),
+    );
+}
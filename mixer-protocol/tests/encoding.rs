@@ -0,0 +1,186 @@
+//! Pins the SCALE layout of every message so the contract and the wallet
+//! can't drift apart again: the first byte of an enum is its variant index.
+
+use mixer_protocol::*;
+use parity_scale_codec::{Decode, Encode};
+
+fn round_trip<T: Encode + Decode + PartialEq + core::fmt::Debug>(value: T, index: u8) {
+    let bytes = value.encode();
+    assert_eq!(bytes[0], index, "{value:?} moved to another variant index");
+    assert_eq!(T::decode(&mut &bytes[..]).unwrap(), value);
+}
+
+const USER: ActorId = ActorId::new([7; 32]);
+
+#[test]
+fn handle_actions() {
+    let call = WithdrawCall {
+        destination: USER,
+        fallback: ActorId::new([8; 32]),
+        payload: vec![1, 2, 3],
+    };
+    round_trip(ContractHandleAction::Deposit { hashes: vec![[1; 32]] }, 0);
+    round_trip(ContractHandleAction::Withdraw { image_id_receipt: vec![2; 8] }, 1);
+    round_trip(ContractHandleAction::Enqueue { hashes: vec![[3; 32]] }, 2);
+    round_trip(ContractHandleAction::InsertBatch { image_id_receipt: vec![4] }, 3);
+    round_trip(
+        ContractHandleAction::ExportState {
+            section: StateSection::Roots,
+            from: 5,
+            limit: 6,
+        },
+        4,
+    );
+    round_trip(ContractHandleAction::BeginImport { predecessor: USER }, 5);
+    round_trip(
+        ContractHandleAction::ImportState {
+            chunk: StateChunk::Nullifiers(vec![[9; 32]]),
+        },
+        6,
+    );
+    round_trip(ContractHandleAction::Handover { successor: USER }, 7);
+    round_trip(ContractHandleAction::AcceptHandover { leaves: 3, nullifiers: 1 }, 8);
+    round_trip(
+        ContractHandleAction::WithdrawAndCall {
+            image_id_receipt: vec![10],
+            call,
+        },
+        9,
+    );
+    round_trip(
+        ContractHandleAction::SetReceiptPolicy {
+            policy: ReceiptPolicy {
+                max_receipt_bytes: 11,
+                accepted_kinds: vec![ReceiptKind::Succinct, ReceiptKind::Groth16],
+            },
+        },
+        10,
+    );
+    round_trip(ContractHandleAction::Freeze, 11);
+}
+
+#[test]
+fn handle_events() {
+    round_trip(ContractHandleEvent::Deposited, 0);
+    round_trip(ContractHandleEvent::WrongDeposit, 1);
+    round_trip(ContractHandleEvent::Withdrawed, 2);
+    round_trip(ContractHandleEvent::Enqueued, 3);
+    round_trip(ContractHandleEvent::BatchInserted, 4);
+    round_trip(
+        ContractHandleEvent::StateExported {
+            chunk: StateChunk::Leaves(vec![[1; 32]]),
+            total: 1,
+        },
+        5,
+    );
+    round_trip(ContractHandleEvent::ImportStarted, 6);
+    round_trip(ContractHandleEvent::StateImported, 7);
+    round_trip(ContractHandleEvent::HandedOver, 8);
+    round_trip(ContractHandleEvent::HandoverAccepted, 9);
+    round_trip(ContractHandleEvent::WithdrawnAndCalled { success: true }, 10);
+    round_trip(ContractHandleEvent::WithdrawnSplit, 11);
+    round_trip(ContractHandleEvent::ReceiptPolicySet, 12);
+    round_trip(ContractHandleEvent::Frozen, 13);
+}
+
+#[test]
+fn state_sections() {
+    round_trip(StateSection::Leaves, 0);
+    round_trip(StateSection::Roots, 1);
+    round_trip(StateSection::Nullifiers, 2);
+    round_trip(StateSection::FixedRoots, 3);
+    round_trip(StateChunk::Leaves(vec![[0; 32]]), 0);
+    round_trip(StateChunk::Roots(vec![([1; 32], 1)]), 1);
+    round_trip(StateChunk::Nullifiers(vec![[2; 32]]), 2);
+    round_trip(StateChunk::FixedRoots(vec![[3; 32]]), 3);
+}
+
+#[test]
+fn receipt_kinds() {
+    // `ReceiptKind::of` and the bundle's kind byte rely on these indices too.
+    for (kind, index) in [
+        (ReceiptKind::Composite, 0),
+        (ReceiptKind::Succinct, 1),
+        (ReceiptKind::Groth16, 2),
+        (ReceiptKind::Fake, 3),
+    ] {
+        round_trip(kind, index);
+        assert_eq!(ReceiptKind::from_u8(index), Some(kind));
+    }
+}
+
+#[test]
+fn state_payloads() {
+    round_trip(StatePayload::Root, 0);
+    round_trip(StatePayload::Leaves, 1);
+    round_trip(StatePayload::LeavesLen, 2);
+    round_trip(StatePayload::Withdrawn { from: 3 }, 3);
+    round_trip(StatePayload::WithdrawnAll, 4);
+    round_trip(StatePayload::HistoryOneFrom { user: USER, from: 5 }, 5);
+    round_trip(StatePayload::HistoryFrom(vec![(USER, 6)]), 6);
+    round_trip(StatePayload::HistoryAll, 7);
+    round_trip(StatePayload::IsSpent { nullifiers: vec![[8; 32]] }, 8);
+    round_trip(StatePayload::LeafIndexOf { commitments: vec![[9; 32]] }, 9);
+    round_trip(StatePayload::Queue, 10);
+    round_trip(StatePayload::Frontier, 11);
+    round_trip(StatePayload::Migration, 12);
+    round_trip(StatePayload::LeavesPage { from: 13, limit: 2 }, 13);
+    round_trip(StatePayload::FixedTree, 14);
+    round_trip(
+        StatePayload::KnownRoot {
+            root: [15; 32],
+            tree: MembershipTree::AppendOnly { leaves: 16 },
+        },
+        15,
+    );
+}
+
+#[test]
+fn state_outputs() {
+    let history = vec![TransactionInfo { amount: -2, time: 3 }];
+    round_trip(StateOutput::Root { res: [0; 32] }, 0);
+    round_trip(StateOutput::Leaves { res: vec![[1; 32]] }, 1);
+    round_trip(StateOutput::LeavesLen { res: 2 }, 2);
+    round_trip(StateOutput::Withdrawn { res: vec![[3; 32]] }, 3);
+    round_trip(StateOutput::WithdrawnAll { res: vec![[4; 32]] }, 4);
+    round_trip(StateOutput::HistoryOneFrom { res: history.clone() }, 5);
+    round_trip(StateOutput::HistoryFrom { res: vec![(USER, history.clone())] }, 6);
+    round_trip(StateOutput::HistoryAll { res: vec![(USER, history)] }, 7);
+    round_trip(StateOutput::IsSpent { res: vec![0b101] }, 8);
+    round_trip(StateOutput::LeafIndexOf { res: vec![Some(9), None] }, 9);
+    round_trip(StateOutput::Queue { res: vec![[10; 32]] }, 10);
+    round_trip(
+        StateOutput::Frontier {
+            peaks: vec![[11; 32]],
+            leaves_len: 1,
+        },
+        11,
+    );
+    round_trip(
+        StateOutput::Migration {
+            admin: USER,
+            frozen: true,
+            importing_from: None,
+        },
+        12,
+    );
+    round_trip(StateOutput::LeavesPage { res: vec![[13; 32]] }, 13);
+    round_trip(
+        StateOutput::FixedTree {
+            filled: vec![[14; 32]],
+            root: [15; 32],
+            leaves_len: 1,
+        },
+        14,
+    );
+    round_trip(StateOutput::KnownRoot { res: true }, 15);
+}
+
+#[test]
+fn actor_id_is_raw_bytes() {
+    assert_eq!(USER.encode(), [7; 32]);
+    assert_eq!(
+        StatePayload::HistoryOneFrom { user: USER, from: 1 }.encode(),
+        [&[5][..], &[7; 32], &1u64.to_le_bytes()].concat()
+    );
+}
@@ -0,0 +1,201 @@
+//! Golden vectors for the v2 journal layout. Changing any of them means the
+//! guests, the contract and every stored receipt disagree: bump the version.
+
+use mixer_protocol::*;
+
+const WITHDRAW_PLAIN: &str = concat!(
+    "0200111111111111111111111111111111111111111111111111111111111111",
+    "1111000500000000000000010000002222222222222222222222222222222222",
+    "2222222222222222222222222222220000000000",
+);
+
+const WITHDRAW_FULL: &str = concat!(
+    "0200aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa",
+    "aaaa010200000001010101010101010101010101010101010101010101010101",
+    "0101010101010102020202020202020202020202020202020202020202020202",
+    "0202020202020201cccccccccccccccccccccccccccccccccccccccccccccccc",
+    "cccccccccccccccc020000000d0d0d0d0d0d0d0d0d0d0d0d0d0d0d0d0d0d0d0d",
+    "0d0d0d0d0d0d0d0d0d0d0d0d010000000e0e0e0e0e0e0e0e0e0e0e0e0e0e0e0e",
+    "0e0e0e0e0e0e0e0e0e0e0e0e0e0e0e0e01000000",
+);
+
+const BATCH: &str = concat!(
+    "0201333333333333333333333333333333333333333333333333333333333333",
+    "3333050000000000000003000000000000004444444444444444444444444444",
+    "4444444444444444444444444444444444445555555555555555555555555555",
+    "5555555555555555555555555555555555550200000066666666666666666666",
+    "6666666666666666666666666666666666666666666677777777777777777777",
+    "7777777777777777777777777777777777777777777788888888888888888888",
+    "8888888888888888888888888888888888888888888899999999999999999999",
+    "9999999999999999999999999999999999999999999902000000aaaaaaaaaaaa",
+    "aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaabbbbbbbbbbbb",
+    "bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb",
+);
+
+const AGGREGATE: &str = concat!(
+    "0202010000000200000003000000040000000500000006000000070000000800",
+    "0000111111111111111111111111111111111111111111111111111111111111",
+    "1111000500000000000000010000002222222222222222222222222222222222",
+    "2222222222222222222222222222220000000000",
+);
+
+const OWNERSHIP_DISCLOSED: &str = concat!(
+    "0203111111111111111111111111111111111111111111111111111111111111",
+    "1111000800000000000000222222222222222222222222222222222222222222",
+    "2222222222222222222222010700000000000000333333333333333333333333",
+    "3333333333333333333333333333333333333333",
+);
+
+const OWNERSHIP_HIDDEN: &str = concat!(
+    "0203111111111111111111111111111111111111111111111111111111111111",
+    "1111000800000000000000222222222222222222222222222222222222222222",
+    "222222222222222222222200",
+);
+
+const EXCLUSION: &str = concat!(
+    "0204111111111111111111111111111111111111111111111111111111111111",
+    "1111000800000000000000222222222222222222222222222222222222222222",
+    "2222222222222222222222000144444444444444444444444444444444444444",
+    "44444444444444444444444444",
+);
+
+fn withdraw_plain() -> PublicOutputs {
+    PublicOutputs {
+        root: [0x11; 32],
+        tree: MembershipTree::AppendOnly { leaves: 5 },
+        used: vec![[0x22; 32]],
+        call: None,
+        outputs: vec![],
+    }
+}
+
+fn withdraw_full() -> PublicOutputs {
+    PublicOutputs {
+        root: [0xaa; 32],
+        tree: MembershipTree::Fixed,
+        used: vec![[0x01; 32], [0x02; 32]],
+        call: Some([0xcc; 32]),
+        outputs: vec![
+            WithdrawOutput {
+                recipient: [0x0d; 32],
+                note_count: 1,
+            },
+            WithdrawOutput {
+                recipient: [0x0e; 32],
+                note_count: 1,
+            },
+        ],
+    }
+}
+
+fn batch() -> BatchOutputs {
+    BatchOutputs {
+        old_root: [0x33; 32],
+        old_len: 5,
+        count: 3,
+        batch_hash: [0x44; 32],
+        new_root: [0x55; 32],
+        new_peaks: vec![[0x66; 32], [0x77; 32]],
+        old_fixed: [0x88; 32],
+        new_fixed_root: [0x99; 32],
+        new_filled: vec![[0xaa; 32], [0xbb; 32]],
+    }
+}
+
+#[test]
+fn withdraw_vectors() {
+    for (vector, outputs) in [(WITHDRAW_PLAIN, withdraw_plain()), (WITHDRAW_FULL, withdraw_full())] {
+        let bytes = hex::decode(vector).unwrap();
+        assert_eq!(outputs.to_journal(), bytes);
+        assert_eq!(PublicOutputs::from_journal(&bytes), Ok(outputs));
+    }
+}
+
+#[test]
+fn batch_vector() {
+    let bytes = hex::decode(BATCH).unwrap();
+    assert_eq!(batch().to_journal(), bytes);
+    assert_eq!(BatchOutputs::from_journal(&bytes), Ok(batch()));
+}
+
+fn aggregate() -> AggregateOutputs {
+    AggregateOutputs {
+        note_image_id: [1, 2, 3, 4, 5, 6, 7, 8],
+        withdraw: withdraw_plain(),
+    }
+}
+
+#[test]
+fn aggregate_vector() {
+    let bytes = hex::decode(AGGREGATE).unwrap();
+    assert_eq!(aggregate().to_journal(), bytes);
+    assert_eq!(AggregateOutputs::from_journal(&bytes), Ok(aggregate()));
+    assert_eq!(PublicOutputs::from_journal(&bytes), Err(JournalError::WrongKind(2)));
+}
+
+#[test]
+fn ownership_vectors() {
+    let disclosed = OwnershipOutputs {
+        root: [0x11; 32],
+        tree: MembershipTree::AppendOnly { leaves: 8 },
+        challenge: [0x22; 32],
+        leaf: Some(DisclosedLeaf {
+            index: 7,
+            commitment: [0x33; 32],
+        }),
+        blocklist: None,
+    };
+    let hidden = OwnershipOutputs { leaf: None, ..disclosed };
+    let excluded = OwnershipOutputs {
+        leaf: None,
+        blocklist: Some(BlocklistCommitment::Sparse([0x44; 32])),
+        ..disclosed
+    };
+    for (vector, outputs) in [
+        (OWNERSHIP_DISCLOSED, disclosed),
+        (OWNERSHIP_HIDDEN, hidden),
+        (EXCLUSION, excluded),
+    ] {
+        let bytes = hex::decode(vector).unwrap();
+        assert_eq!(outputs.to_journal(), bytes);
+        assert_eq!(OwnershipOutputs::from_journal(&bytes), Ok(outputs));
+        assert!(matches!(PublicOutputs::from_journal(&bytes), Err(JournalError::WrongKind(3 | 4))));
+    }
+}
+
+#[test]
+fn rejects_malformed_journals() {
+    let bytes = hex::decode(WITHDRAW_FULL).unwrap();
+
+    assert_eq!(PublicOutputs::from_journal(&[]), Err(JournalError::UnexpectedEnd));
+    for len in 0..bytes.len() {
+        assert!(PublicOutputs::from_journal(&bytes[..len]).is_err());
+    }
+
+    let mut trailing = bytes.clone();
+    trailing.push(0);
+    assert_eq!(PublicOutputs::from_journal(&trailing), Err(JournalError::TrailingBytes(1)));
+
+    let mut version = bytes.clone();
+    version[0] = JOURNAL_VERSION + 1;
+    assert_eq!(
+        PublicOutputs::from_journal(&version),
+        Err(JournalError::UnsupportedVersion(JOURNAL_VERSION + 1))
+    );
+
+    let batch = hex::decode(BATCH).unwrap();
+    assert_eq!(PublicOutputs::from_journal(&batch), Err(JournalError::WrongKind(1)));
+    assert_eq!(BatchOutputs::from_journal(&bytes), Err(JournalError::WrongKind(0)));
+
+    let mut tag = bytes.clone();
+    tag[2 + 32 + 1 + 4 + 64] = 2;
+    assert_eq!(PublicOutputs::from_journal(&tag), Err(JournalError::InvalidTag(2)));
+
+    let mut tree = bytes.clone();
+    tree[2 + 32] = 2;
+    assert_eq!(PublicOutputs::from_journal(&tree), Err(JournalError::InvalidTag(2)));
+
+    let mut huge = hex::decode(WITHDRAW_PLAIN).unwrap();
+    huge[43..47].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert_eq!(PublicOutputs::from_journal(&huge), Err(JournalError::UnexpectedEnd));
+}
@@ -0,0 +1,85 @@
+use mixer_protocol::{
+    rs_merkle::Hasher, test_vectors, zero_hashes, FixedTree, Frontier, MerklePath, Sha256Fn, TaggedHasher, LEAF_TAG,
+    NODE_TAG, TREE_DEPTH,
+};
+use sha2::{Digest, Sha256};
+
+#[derive(Clone)]
+struct Sha2;
+
+impl Sha256Fn for Sha2 {
+    fn sha256(data: &[u8]) -> [u8; 32] {
+        Sha256::digest(data).into()
+    }
+}
+
+type H = TaggedHasher<Sha2>;
+
+#[test]
+fn vectors() {
+    test_vectors::check::<H>();
+}
+
+#[test]
+fn fixtures() {
+    test_vectors::check_fixtures::<H>(&serde_json::from_str(test_vectors::FIXTURES).unwrap());
+}
+
+#[test]
+fn leaves_and_nodes_are_domain_separated() {
+    let (left, right) = ([1; 32], [2; 32]);
+    let preimage = [left, right].concat();
+    assert_ne!(H::hash(&preimage), H::concat_and_hash(&left, Some(&right)));
+    assert_ne!(H::hash(&preimage), Sha2::sha256(&preimage));
+    assert_eq!(H::hash(&preimage), Sha2::sha256(&[&[LEAF_TAG][..], &preimage].concat()));
+    assert_eq!(
+        H::concat_and_hash(&left, Some(&right)),
+        Sha2::sha256(&[&[NODE_TAG][..], &left, &right].concat())
+    );
+    assert_eq!(H::concat_and_hash(&left, None), left);
+}
+
+
+#[test]
+fn fixed_tree_matches_paths_while_growing() {
+    let zeros = zero_hashes::<H>();
+    let mut tree = FixedTree::default();
+    assert_eq!(tree.root(&zeros), zeros[TREE_DEPTH]);
+
+    let leaves: Vec<[u8; 32]> = (0..37u8).map(|i| H::hash(&[i])).collect();
+    for len in 1..=leaves.len() {
+        tree.append::<H>(&zeros, leaves[len - 1]);
+        for index in [0, len / 2, len - 1] {
+            let path = MerklePath::build::<H>(&leaves[..len], index as u64);
+            assert_eq!(path.siblings.len(), TREE_DEPTH);
+            assert_eq!(path.root::<H>(leaves[index]), tree.root(&zeros));
+        }
+    }
+}
+
+#[test]
+fn fixed_tree_commitment_covers_filled() {
+    let zeros = zero_hashes::<H>();
+    let mut tree = FixedTree::default();
+    (0..3u8).for_each(|i| tree.append::<H>(&zeros, H::hash(&[i])));
+
+    // Same root and size, but appending to it would give another root.
+    let mut forged = tree.clone();
+    forged.filled[1] = [0xff; 32];
+    assert_ne!(forged.commitment::<H>(), tree.commitment::<H>());
+}
+
+#[test]
+fn frontier_has_a_peak_per_set_bit() {
+    let mut frontier = Frontier::default();
+    for i in 0..13u8 {
+        assert!(frontier.is_well_formed());
+        frontier.append::<H>(H::hash(&[i]));
+    }
+
+    // A peak `len` has no bit for would be hashed into every later root.
+    let mut forged = frontier.clone();
+    let last = forged.peaks.pop().unwrap();
+    forged.peaks.extend([last, [0; 32]]);
+    assert!(!forged.is_well_formed());
+}
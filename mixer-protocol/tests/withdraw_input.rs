@@ -0,0 +1,100 @@
+use mixer_protocol::{
+    FixedTree, Membership, MerklePath, OwnershipInput, RustShit, WithdrawInput, WithdrawInputError, TREE_DEPTH,
+    OWNERSHIP_INPUT_VERSION, WITHDRAW_INPUT_VERSION,
+};
+
+fn notes(indices: &[u64]) -> Vec<RustShit> {
+    indices.iter().map(|&i| RustShit([i as u8; 32], [!(i as u8); 32])).collect()
+}
+
+fn input(indices: Vec<u64>, total_leaves_count: u64) -> WithdrawInput {
+    WithdrawInput::new(vec![], indices.clone(), notes(&indices), total_leaves_count)
+}
+
+fn paths_input(indices: Vec<u64>) -> WithdrawInput {
+    let paths = indices
+        .iter()
+        .map(|&index| MerklePath {
+            index,
+            siblings: vec![[0; 32]; TREE_DEPTH],
+        })
+        .collect();
+    WithdrawInput::with_paths(paths, notes(&indices))
+}
+
+#[test]
+fn preimage_is_nullifier_then_secret() {
+    let note = RustShit([1; 32], [2; 32]);
+    let preimage = note.to_preimage();
+    assert_eq!(preimage[..32], [1; 32]);
+    assert_eq!(preimage[32..], [2; 32]);
+    assert_eq!(RustShit::from(preimage), note);
+}
+
+#[test]
+fn accepts_sorted_unique_indices() {
+    assert_eq!(input(vec![0, 3, 9], 10).validate(), Ok(()));
+}
+
+#[test]
+fn rejects_malformed_indices() {
+    assert_eq!(input(vec![3, 1], 10).validate(), Err(WithdrawInputError::UnsortedIndices));
+    assert_eq!(input(vec![2, 2], 10).validate(), Err(WithdrawInputError::DuplicateIndex(2)));
+    assert_eq!(
+        input(vec![1, 10], 10).validate(),
+        Err(WithdrawInputError::IndexOutOfRange { index: 10, total_leaves_count: 10 })
+    );
+    assert_eq!(input(vec![], 10).validate(), Err(WithdrawInputError::NoNotes));
+
+    let mut mismatched = input(vec![1, 2], 10);
+    mismatched.notes.pop();
+    assert_eq!(
+        mismatched.validate(),
+        Err(WithdrawInputError::LengthMismatch { indices: 2, notes: 1 })
+    );
+
+    let mut future = input(vec![1], 10);
+    future.version = WITHDRAW_INPUT_VERSION + 1;
+    assert_eq!(future.validate(), Err(WithdrawInputError::UnsupportedVersion(WITHDRAW_INPUT_VERSION + 1)));
+}
+
+#[test]
+fn validates_fixed_depth_paths() {
+    assert_eq!(paths_input(vec![0, 5, FixedTree::CAPACITY - 1]).validate(), Ok(()));
+    assert_eq!(paths_input(vec![5, 0]).validate(), Err(WithdrawInputError::UnsortedIndices));
+    assert_eq!(
+        paths_input(vec![FixedTree::CAPACITY]).validate(),
+        Err(WithdrawInputError::IndexOutOfRange {
+            index: FixedTree::CAPACITY,
+            total_leaves_count: FixedTree::CAPACITY
+        })
+    );
+
+    let mut short = paths_input(vec![3]);
+    if let Membership::Paths(paths) = &mut short.membership {
+        paths[0].siblings.pop();
+    }
+    assert_eq!(
+        short.validate(),
+        Err(WithdrawInputError::WrongPathDepth { index: 3, depth: TREE_DEPTH - 1 })
+    );
+}
+
+#[test]
+fn ownership_proves_a_single_note() {
+    let attest = |input: WithdrawInput| OwnershipInput::new(input.membership, input.notes[0], [9; 32], false);
+    assert_eq!(attest(input(vec![4], 10)).validate(), Ok(()));
+    assert_eq!(attest(paths_input(vec![4])).validate(), Ok(()));
+    assert_eq!(
+        attest(input(vec![4, 5], 10)).validate(),
+        Err(WithdrawInputError::LengthMismatch { indices: 2, notes: 1 })
+    );
+    assert_eq!(
+        attest(input(vec![10], 10)).validate(),
+        Err(WithdrawInputError::IndexOutOfRange { index: 10, total_leaves_count: 10 })
+    );
+
+    let mut future = attest(input(vec![4], 10));
+    future.version = OWNERSHIP_INPUT_VERSION + 1;
+    assert_eq!(future.validate(), Err(WithdrawInputError::UnsupportedVersion(OWNERSHIP_INPUT_VERSION + 1)));
+}
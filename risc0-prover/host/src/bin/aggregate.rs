@@ -0,0 +1,82 @@
+use std::time::Instant;
+
+use methods::{
+    AGGREGATE_GUEST_ELF, AGGREGATE_GUEST_ID, HELLO_GUEST_ELF, HELLO_GUEST_ID
+};
+use risc0_zkvm::{default_prover, ExecutorEnv, ProverOpts, Receipt};
+use rs_merkle::{Hasher, MerkleTree};
+use mixer_protocol::{AggregateInput, AggregateOutputs, MerkleHasher, RustShit, WithdrawInput};
+
+// Proves one note on its own, so its nullifier is bound to no other note until aggregation.
+fn prove_note(tree: &MerkleTree<MerkleHasher>, index: usize, note: RustShit) -> Receipt {
+    let input = WithdrawInput::new(
+        tree.proof(&[index]).to_bytes(),
+        vec![index as u64],
+        vec![note],
+        tree.leaves_len() as u64,
+    );
+    input.validate().unwrap();
+
+    let env = ExecutorEnv::builder()
+        .write(&input)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    default_prover()
+        .prove_with_opts(env, HELLO_GUEST_ELF, &ProverOpts::succinct())
+        .unwrap()
+        .receipt
+}
+
+fn main(){
+    let elems: Vec<RustShit> = (0..10).map(|x: u32| RustShit([x as u8; 32], [x as u8; 32])).collect();
+    let leaves: Vec<[u8; 32]> = elems
+        .iter()
+        .map(|x| MerkleHasher::hash(&x.to_preimage()))
+        .collect();
+    let merkle_tree = MerkleTree::<MerkleHasher>::from_leaves(&leaves);
+    let merkle_root = merkle_tree.root().unwrap();
+
+    let start_time = Instant::now();
+    let note_receipts: Vec<Receipt> = (6..8).map(|i| prove_note(&merkle_tree, i, elems[i])).collect();
+    let time_for_notes = start_time.elapsed().as_millis();
+
+    let input = AggregateInput{
+        note_image_id: HELLO_GUEST_ID,
+        journals: note_receipts.iter().map(|receipt| receipt.journal.bytes.clone()).collect(),
+    };
+    let mut builder = ExecutorEnv::builder();
+    for receipt in note_receipts{
+        builder.add_assumption(receipt);
+    }
+    let env = builder
+        .write(&input)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let start_time = Instant::now();
+    let receipt = default_prover()
+        .prove_with_opts(env, AGGREGATE_GUEST_ELF, &ProverOpts::succinct())
+        .unwrap()
+        .receipt;
+    let time_for_aggregate = start_time.elapsed().as_millis();
+
+    let output = AggregateOutputs::from_journal(&receipt.journal.bytes).unwrap();
+
+    let start_time = Instant::now();
+    let verified = receipt.verify(AGGREGATE_GUEST_ID);
+    let time_for_verify = start_time.elapsed().as_millis();
+
+    match verified {
+        Ok(..) if output.withdraw.root == merkle_root && output.note_image_id == HELLO_GUEST_ID => {
+            println!(
+                "time for note proofs {} ms\ntime for aggregation {} ms\ntime for verify {} ms",
+                time_for_notes, time_for_aggregate, time_for_verify
+            );
+        }
+        Ok(..) => println!("output_hash = {:?}, real_hash = {:?}", output.withdraw.root, merkle_root),
+        _ => println!("Wrong proof")
+    };
+}
@@ -0,0 +1,59 @@
+use std::time::Instant;
+
+use methods::{
+    BATCH_GUEST_ELF, BATCH_GUEST_ID
+};
+use risc0_zkvm::{default_prover, ExecutorEnv};
+use rs_merkle::MerkleTree;
+use mixer_protocol::{zero_hashes, BatchOutputs, FixedTree, Frontier, MerkleHasher};
+
+fn main(){
+    let leaves: Vec<[u8; 32]> = (0..10).map(|x: u32| [x as u8; 32]).collect();
+    let (inserted, queued) = leaves.split_at(6);
+
+    let mut frontier = Frontier::default();
+    inserted.iter().for_each(|leaf| frontier.append::<MerkleHasher>(*leaf));
+    let old_root = frontier.root::<MerkleHasher>();
+
+    let zeros = zero_hashes::<MerkleHasher>();
+    let mut fixed_tree = FixedTree::default();
+    inserted.iter().for_each(|leaf| fixed_tree.append::<MerkleHasher>(&zeros, *leaf));
+    let old_fixed = fixed_tree.commitment::<MerkleHasher>();
+
+    let env = ExecutorEnv::builder()
+        .write(&frontier)
+        .unwrap()
+        .write(&fixed_tree)
+        .unwrap()
+        .write(&queued.to_vec())
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let prover = default_prover();
+
+    let start_time = Instant::now();
+    let receipt = prover
+        .prove(env, BATCH_GUEST_ELF)
+        .unwrap();
+    let time_for_proof = start_time.elapsed().as_millis();
+
+    let output = BatchOutputs::from_journal(&receipt.receipt.journal.bytes).unwrap();
+
+    let start_time = Instant::now();
+    let verified = receipt.receipt.verify(BATCH_GUEST_ID);
+    let time_for_verify = start_time.elapsed().as_millis();
+
+    let real_root = MerkleTree::<MerkleHasher>::from_leaves(&leaves).root().unwrap();
+    queued.iter().for_each(|leaf| fixed_tree.append::<MerkleHasher>(&zeros, *leaf));
+    let fixed_ok = output.old_fixed == old_fixed
+        && output.new_fixed_root == fixed_tree.root(&zeros)
+        && output.new_filled == fixed_tree.filled;
+    match verified {
+        Ok(..) if output.old_root == old_root && output.new_root == real_root && fixed_ok => {
+            println!("time for creating batch proof {} ms\ntime for verify {} ms", time_for_proof, time_for_verify);
+        }
+        Ok(..) => println!("output_hash = {:?}, real_hash = {:?}", output.new_root, real_root),
+        _ => println!("Wrong proof")
+    };
+}
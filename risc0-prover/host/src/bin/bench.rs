@@ -0,0 +1,222 @@
+//! Sweeps withdrawals over the number of spent notes and the tree size,
+//! recording cycles, segments, proving and verification time and receipt
+//! size, and compares the results against a saved baseline.
+//!
+//! ```sh
+//! cargo run --release --bin bench -- --execute-only --json bench.json
+//! cargo run --release --bin bench -- --baseline bench.json
+//! ```
+
+use std::{collections::HashMap, error::Error, fs, path::PathBuf, process::ExitCode, time::Instant};
+
+use clap::Parser;
+use methods::{HELLO_GUEST_ELF, HELLO_GUEST_ID};
+use mixer_protocol::{
+    rs_merkle::{Hasher, MerkleTree},
+    MerkleHasher, MerklePath, RustShit, WithdrawInput,
+};
+use risc0_zkvm::{default_executor, default_prover, ExecutorEnv};
+use serde::{Deserialize, Serialize};
+
+#[derive(Parser)]
+#[command(name = "bench", about = "Withdraw proving benchmark")]
+struct Args {
+    /// Numbers of spent notes to sweep.
+    #[arg(long, value_delimiter = ',', default_values_t = [1, 2, 4, 8, 16, 32, 64])]
+    notes: Vec<usize>,
+    /// Tree sizes to sweep.
+    #[arg(long, value_delimiter = ',', default_values_t = [10, 1_000, 100_000, 1_000_000])]
+    leaves: Vec<usize>,
+    /// Use fixed-depth paths instead of a multiproof.
+    #[arg(long)]
+    fixed: bool,
+    /// Only run the executor: cycles and segments, no proving.
+    #[arg(long)]
+    execute_only: bool,
+    #[arg(long)]
+    json: Option<PathBuf>,
+    #[arg(long)]
+    csv: Option<PathBuf>,
+    /// JSON results of an earlier run to compare against.
+    #[arg(long)]
+    baseline: Option<PathBuf>,
+    /// Growth in cycles, segments or receipt size, in percent, that counts as a regression.
+    #[arg(long, default_value_t = 5.0)]
+    tolerance: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Record {
+    notes: usize,
+    leaves: usize,
+    membership: String,
+    cycles: u64,
+    segments: usize,
+    prove_ms: Option<u128>,
+    verify_ms: Option<u128>,
+    receipt_bytes: Option<usize>,
+}
+
+impl Record {
+    fn key(&self) -> (usize, usize, String) {
+        (self.notes, self.leaves, self.membership.clone())
+    }
+}
+
+fn note(index: usize) -> RustShit {
+    let mut nullifier = [0; 32];
+    nullifier[..8].copy_from_slice(&(index as u64).to_le_bytes());
+    let mut secret = nullifier;
+    secret[31] = 0xff;
+    RustShit(nullifier, secret)
+}
+
+fn input(leaves: &[[u8; 32]], tree: &MerkleTree<MerkleHasher>, notes: usize, fixed: bool) -> WithdrawInput {
+    // Spread the spent notes over the whole tree so the proof shares few nodes.
+    let indices: Vec<u64> = (0..notes).map(|i| (i * leaves.len() / notes) as u64).collect();
+    let spent = indices.iter().map(|&index| note(index as usize)).collect();
+
+    let input = if fixed {
+        let paths = indices.iter().map(|&index| MerklePath::build::<MerkleHasher>(leaves, index)).collect();
+        WithdrawInput::with_paths(paths, spent)
+    } else {
+        let proof_indices: Vec<usize> = indices.iter().map(|&index| index as usize).collect();
+        WithdrawInput::new(tree.proof(&proof_indices).to_bytes(), indices, spent, leaves.len() as u64)
+    };
+    input.validate().unwrap();
+    input
+}
+
+fn run(input: &WithdrawInput, leaves: usize, fixed: bool, execute_only: bool) -> Result<Record, Box<dyn Error>> {
+    let env = ExecutorEnv::builder().write(input)?.build()?;
+    let session = default_executor().execute(env, HELLO_GUEST_ELF)?;
+
+    let mut record = Record {
+        notes: input.notes.len(),
+        leaves,
+        membership: if fixed { "paths" } else { "multiproof" }.into(),
+        cycles: session.cycles(),
+        segments: session.segments.len(),
+        prove_ms: None,
+        verify_ms: None,
+        receipt_bytes: None,
+    };
+    if execute_only {
+        return Ok(record);
+    }
+
+    let env = ExecutorEnv::builder().write(input)?.build()?;
+    let start = Instant::now();
+    let receipt = default_prover().prove(env, HELLO_GUEST_ELF)?.receipt;
+    record.prove_ms = Some(start.elapsed().as_millis());
+
+    let start = Instant::now();
+    receipt.verify(HELLO_GUEST_ID)?;
+    record.verify_ms = Some(start.elapsed().as_millis());
+    record.receipt_bytes = Some(postcard::to_allocvec(&(HELLO_GUEST_ID, receipt))?.len());
+
+    Ok(record)
+}
+
+fn csv(records: &[Record]) -> String {
+    let optional = |value: Option<u128>| value.map(|v| v.to_string()).unwrap_or_default();
+    let mut csv = String::from("notes,leaves,membership,cycles,segments,prove_ms,verify_ms,receipt_bytes\n");
+    for r in records {
+        csv += &format!(
+            "{},{},{},{},{},{},{},{}\n",
+            r.notes,
+            r.leaves,
+            r.membership,
+            r.cycles,
+            r.segments,
+            optional(r.prove_ms),
+            optional(r.verify_ms),
+            optional(r.receipt_bytes.map(|bytes| bytes as u128)),
+        );
+    }
+    csv
+}
+
+/// Prints how every record moved against the baseline and returns how many regressed.
+fn compare(records: &[Record], baseline: &[Record], tolerance: f64) -> usize {
+    let baseline: HashMap<_, _> = baseline.iter().map(|r| (r.key(), r)).collect();
+    let change = |new: u128, old: u128| (new as f64 - old as f64) * 100.0 / (old as f64).max(1.0);
+
+    let mut regressions = 0;
+    for r in records {
+        let Some(old) = baseline.get(&r.key()) else {
+            println!("{:>3} notes {:>8} leaves {:<10} not in baseline", r.notes, r.leaves, r.membership);
+            continue;
+        };
+        let mut checked = vec![
+            ("cycles", change(r.cycles as u128, old.cycles as u128)),
+            ("segments", change(r.segments as u128, old.segments as u128)),
+        ];
+        if let (Some(new), Some(old)) = (r.receipt_bytes, old.receipt_bytes) {
+            checked.push(("receipt", change(new as u128, old as u128)));
+        }
+        let regressed: Vec<String> = checked
+            .iter()
+            .filter(|(_, change)| *change > tolerance)
+            .map(|(name, change)| format!("{name} {change:+.1}%"))
+            .collect();
+        let timing = match (r.prove_ms, old.prove_ms) {
+            (Some(new), Some(old)) => format!(", prove {:+.1}%", change(new, old)),
+            _ => String::new(),
+        };
+        println!(
+            "{:>3} notes {:>8} leaves {:<10} cycles {:+.1}%{timing}{}",
+            r.notes,
+            r.leaves,
+            r.membership,
+            checked[0].1,
+            if regressed.is_empty() { String::new() } else { format!("  REGRESSED: {}", regressed.join(", ")) },
+        );
+        regressions += !regressed.is_empty() as usize;
+    }
+    regressions
+}
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    let args = Args::parse();
+
+    let mut records = Vec::new();
+    for &leaves_len in args.leaves.iter() {
+        let leaves: Vec<[u8; 32]> = (0..leaves_len).map(|i| MerkleHasher::hash(&note(i).to_preimage())).collect();
+        let tree = MerkleTree::<MerkleHasher>::from_leaves(&leaves);
+
+        for &notes in args.notes.iter().filter(|&&notes| notes <= leaves_len) {
+            let record = run(&input(&leaves, &tree, notes, args.fixed), leaves_len, args.fixed, args.execute_only)?;
+            println!(
+                "{:>3} notes {:>8} leaves: {} cycles, {} segments{}",
+                record.notes,
+                record.leaves,
+                record.cycles,
+                record.segments,
+                match (record.prove_ms, record.verify_ms, record.receipt_bytes) {
+                    (Some(prove), Some(verify), Some(bytes)) => format!(", proved in {prove} ms, verified in {verify} ms, {bytes} bytes"),
+                    _ => String::new(),
+                },
+            );
+            records.push(record);
+        }
+    }
+
+    if let Some(path) = &args.json {
+        fs::write(path, serde_json::to_string_pretty(&records)?)?;
+    }
+    if let Some(path) = &args.csv {
+        fs::write(path, csv(&records))?;
+    }
+
+    if let Some(path) = &args.baseline {
+        let baseline: Vec<Record> = serde_json::from_slice(&fs::read(path)?)?;
+        let regressions = compare(&records, &baseline, args.tolerance);
+        if regressions > 0 {
+            println!("{regressions} regressions beyond {}%", args.tolerance);
+            return Ok(ExitCode::FAILURE);
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,103 @@
+//! Runs the withdraw guest on a witness, a JSON `WithdrawInput` as
+//! `mixer-prover` takes it, and writes a pprof profile of where its cycles go
+//! along with a breakdown by phase: input deserialization, leaf hashing, root
+//! computation and the journal commit. The breakdown needs a guest built with
+//! `MIXER_PROFILE_GUESTS=1`.
+//!
+//! ```sh
+//! MIXER_PROFILE_GUESTS=1 cargo run --release --bin profile -- witness.json --pprof guest.pb
+//! go tool pprof -http 127.0.0.1:8000 guest.pb
+//! ```
+
+use std::{error::Error, fs, path::PathBuf};
+
+use clap::Parser;
+use methods::HELLO_GUEST_ELF;
+use mixer_protocol::WithdrawInput;
+use risc0_zkvm::{default_executor, ExecutorEnv};
+use serde::Serialize;
+
+/// What the `profile` build of the guest prefixes its phase lines with.
+const PHASE_PREFIX: &str = "mixer-phase ";
+
+#[derive(Parser)]
+#[command(name = "profile", about = "Withdraw guest cycle profiler")]
+struct Args {
+    /// JSON `WithdrawInput` to run the guest on.
+    witness: PathBuf,
+    /// Where to write the pprof profile.
+    #[arg(long, default_value = "guest.pb")]
+    pprof: PathBuf,
+    /// Also write the breakdown as JSON.
+    #[arg(long)]
+    json: Option<PathBuf>,
+}
+
+#[derive(Serialize, Debug)]
+struct Phase {
+    name: String,
+    cycles: u64,
+}
+
+#[derive(Serialize, Debug)]
+struct Breakdown {
+    cycles: u64,
+    segments: usize,
+    phases: Vec<Phase>,
+}
+
+/// Turns the cycle counts the guest printed at the end of each phase into
+/// the cycles spent in it.
+fn phases(stderr: &str) -> Result<Vec<Phase>, Box<dyn Error>> {
+    let mut phases = Vec::new();
+    let mut last = 0;
+    for line in stderr.lines().filter_map(|line| line.strip_prefix(PHASE_PREFIX)) {
+        let (name, at) = line.split_once(' ').ok_or("Malformed phase line")?;
+        let at: u64 = at.parse()?;
+        let cycles = at.checked_sub(last).ok_or_else(|| format!("Phase {name} ends before the one it follows"))?;
+        phases.push(Phase { name: name.into(), cycles });
+        last = at;
+    }
+    Ok(phases)
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+    let input: WithdrawInput = serde_json::from_slice(&fs::read(&args.witness)?)?;
+    if let Err(e) = input.validate() {
+        eprintln!("Warning: the guest will reject this witness: {e}");
+    }
+
+    let mut stderr = Vec::new();
+    let env = ExecutorEnv::builder()
+        .write(&input)?
+        .stderr(&mut stderr)
+        .enable_profiler(&args.pprof)
+        .build()?;
+    let session = default_executor().execute(env, HELLO_GUEST_ELF)?;
+
+    let breakdown = Breakdown {
+        cycles: session.cycles(),
+        segments: session.segments.len(),
+        phases: phases(&String::from_utf8_lossy(&stderr))?,
+    };
+    println!("wrote {}", args.pprof.display());
+    if breakdown.phases.is_empty() {
+        eprintln!("The guest reported no phases, rebuild it with MIXER_PROFILE_GUESTS=1 for the breakdown");
+    }
+
+    let percent = |cycles: u64| cycles as f64 * 100.0 / breakdown.cycles.max(1) as f64;
+    let traced: u64 = breakdown.phases.iter().map(|phase| phase.cycles).sum();
+    let other = breakdown.cycles.saturating_sub(traced);
+    for phase in breakdown.phases.iter() {
+        println!("{:<8} {:>12} {:>6.1}%", phase.name, phase.cycles, percent(phase.cycles));
+    }
+    // Paging, padding to a power of two and anything after the last phase.
+    println!("{:<8} {:>12} {:>6.1}%", "other", other, percent(other));
+    println!("{:<8} {:>12} in {} segments", "total", breakdown.cycles, breakdown.segments);
+
+    if let Some(path) = args.json {
+        fs::write(&path, serde_json::to_string_pretty(&breakdown)?)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,75 @@
+//! Writes the cross-component fixtures in `mixer-protocol/fixtures/vectors.json`:
+//! notes, leaves, roots and multiproofs for every tree size, and the journals
+//! the withdraw guest commits, recorded by executing it without proving.
+//!
+//! ```sh
+//! cargo run --release --features prove,test-vectors --bin vectors
+//! cargo run --release --features prove,test-vectors --bin vectors -- --check
+//! ```
+
+use std::{error::Error, fs, path::PathBuf, process::ExitCode};
+
+use clap::Parser;
+use methods::HELLO_GUEST_ELF;
+use mixer_protocol::{
+    rs_merkle::MerkleTree,
+    test_vectors::{note, Fixtures},
+    MerkleHasher, MerklePath, WithdrawInput,
+};
+use risc0_zkvm::{default_executor, ExecutorEnv};
+
+#[derive(Parser)]
+#[command(name = "vectors", about = "Cross-component test fixture generator")]
+struct Args {
+    #[arg(long, default_value = concat!(env!("CARGO_MANIFEST_DIR"), "/../../mixer-protocol/fixtures/vectors.json"))]
+    out: PathBuf,
+    /// Fail if the fixtures on disk differ from the generated ones instead of writing them.
+    #[arg(long)]
+    check: bool,
+}
+
+fn journal(leaves: &[[u8; 32]], indices: &[u64], fixed: bool) -> Result<Vec<u8>, Box<dyn Error>> {
+    let notes = indices.iter().map(|&index| note(index as u8)).collect();
+    let input = if fixed {
+        let paths = indices.iter().map(|&index| MerklePath::build::<MerkleHasher>(leaves, index)).collect();
+        WithdrawInput::with_paths(paths, notes)
+    } else {
+        let proof_indices: Vec<usize> = indices.iter().map(|&index| index as usize).collect();
+        let proof_bytes = MerkleTree::<MerkleHasher>::from_leaves(leaves).proof(&proof_indices).to_bytes();
+        WithdrawInput::new(proof_bytes, indices.to_vec(), notes, leaves.len() as u64)
+    };
+    input.validate()?;
+
+    let env = ExecutorEnv::builder().write(&input)?.build()?;
+    Ok(default_executor().execute(env, HELLO_GUEST_ELF)?.journal.bytes)
+}
+
+fn main() -> Result<ExitCode, Box<dyn Error>> {
+    let args = Args::parse();
+    let mut error = None;
+    let fixtures = Fixtures::generate::<MerkleHasher>(|leaves, indices, fixed| {
+        journal(leaves, indices, fixed).unwrap_or_else(|e| {
+            error.get_or_insert(format!("withdrawal of {indices:?} in {} leaves: {e}", leaves.len()));
+            Vec::new()
+        })
+    });
+    if let Some(e) = error {
+        Err(e)?;
+    }
+    let json = serde_json::to_string_pretty(&fixtures)? + "\n";
+
+    if args.check {
+        if fs::read_to_string(&args.out)? != json {
+            eprintln!(
+                "{} is stale, regenerate it with `cargo run --features prove,test-vectors --bin vectors`",
+                args.out.display()
+            );
+            return Ok(ExitCode::FAILURE);
+        }
+        println!("{} is up to date", args.out.display());
+    } else {
+        fs::write(&args.out, json)?;
+        println!("wrote {}", args.out.display());
+    }
+    Ok(ExitCode::SUCCESS)
+}
@@ -0,0 +1,78 @@
+
+use std::time::Instant;
+
+use methods::{
+    HELLO_GUEST_ELF, HELLO_GUEST_ID
+};
+use risc0_zkvm::{default_prover, ExecutorEnv};
+use rs_merkle::{Hasher, MerkleTree};
+use mixer_protocol::{zero_hashes, FixedTree, MerkleHasher, MerklePath, PublicOutputs, RustShit, WithdrawInput};
+
+fn main(){
+    // `--fixed` proves with fixed-depth paths instead of an rs_merkle multiproof.
+    let fixed = std::env::args().any(|arg| arg == "--fixed");
+
+    let elems: Vec<RustShit> = (0..10).map(|x: u32| RustShit([x as u8; 32], [x as u8; 32])).collect();
+    let leaves: Vec<[u8; 32]> = elems
+        .iter()
+        .map(|x| MerkleHasher::hash(&x.to_preimage()))
+        .collect();
+
+    let merkle_tree = MerkleTree::<MerkleHasher>::from_leaves(&leaves);
+    let indices_to_prove: Vec<usize> = (6..8).into_iter().collect();
+    let elems_to_prove = elems.get(6..8).ok_or("can't get leaves to prove").unwrap().to_vec();
+
+    let (input, merkle_root) = if fixed {
+        let zeros = zero_hashes::<MerkleHasher>();
+        let mut fixed_tree = FixedTree::default();
+        leaves.iter().for_each(|leaf| fixed_tree.append::<MerkleHasher>(&zeros, *leaf));
+        let paths = indices_to_prove.iter().map(|&i| MerklePath::build::<MerkleHasher>(&leaves, i as u64)).collect();
+        (WithdrawInput::with_paths(paths, elems_to_prove), fixed_tree.root(&zeros))
+    } else {
+        let merkle_proof = merkle_tree.proof(&indices_to_prove);
+        let merkle_root = merkle_tree.root().ok_or("couldn't get the merkle root").unwrap();
+        let input = WithdrawInput::new(
+            merkle_proof.to_bytes(),
+            indices_to_prove.iter().map(|&x| x as u64).collect(),
+            elems_to_prove,
+            leaves.len() as u64,
+        );
+        (input, merkle_root)
+    };
+    input.validate().unwrap();
+
+    let env = ExecutorEnv::builder()
+        .write(&input)
+        .unwrap()
+        .build()
+        .unwrap();
+
+    let prover = default_prover();
+
+    let start_time = Instant::now();
+    let receipt = prover
+        .prove(env, HELLO_GUEST_ELF)
+        .unwrap();
+    let duration = start_time.elapsed();
+    let time_for_proof = duration.as_millis(); 
+
+    let output = PublicOutputs::from_journal(&receipt.receipt.journal.bytes).unwrap();
+
+    let start_time = Instant::now();
+    let a = receipt.receipt.verify(HELLO_GUEST_ID);
+    let duration = start_time.elapsed();
+    let time_for_verify = duration.as_millis(); 
+
+    match a {
+        Ok(..) => {
+            let real_output = merkle_root;
+            if output.root == real_output {
+                println!("time for creating proof {} ms\ntime for verify {} ms", time_for_proof, time_for_verify);
+            }
+            else{
+                println!("output_hash = {:?}, real_hash = {:?}", output.root, real_output)
+            }
+        }
+        _ => println!("Wrong proof")
+    };
+}
\ No newline at end of file
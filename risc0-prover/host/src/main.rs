@@ -1,102 +1,440 @@
+use std::{
+    error::Error,
+    fs,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
 
-use std::time::Instant;
+use clap::{Parser, Subcommand};
+use gclient::{GearApi, WSAddress};
+use mixer_backend::ProverConfig;
+use mixer_client::MixerClient;
+use mixer_protocol::{ActorId, BlocklistCommitment, MembershipTree, MerkleHasher, PublicOutputs, ReceiptBundle};
 
-use methods::{
-    HELLO_GUEST_ELF, HELLO_GUEST_ID
-};
-use risc0_zkvm::{default_prover, sha::{Impl, Sha256}, ExecutorEnv};
-use rs_merkle::{Hasher, MerkleTree};
-use shared::{PublicOutputs, RustShit};
+use notes::{Note, Store};
+
+mod notes;
+mod prove;
+mod witness;
+
+const LEAVES_PAGE: u32 = 1024;
+
+#[derive(Parser)]
+#[command(name = "mixer-cli", about = "Command-line client for the Mixer program")]
+struct Cli {
+    /// Node WebSocket address.
+    #[arg(long, env = "MIXER_NODE", default_value = "ws://127.0.0.1", global = true)]
+    node: String,
+    #[arg(long, env = "MIXER_PORT", default_value_t = 9944, global = true)]
+    port: u16,
+    /// `Mixer` program id, hex encoded.
+    #[arg(long, env = "MIXER_PROGRAM", global = true)]
+    program: Option<String>,
+    /// Signer secret URI, e.g. `//Alice` or a mnemonic.
+    #[arg(long, env = "MIXER_SURI", default_value = "//Alice", global = true, hide_env_values = true)]
+    suri: String,
+    /// Directory holding `notes.json` and the synced `leaves.bin`.
+    #[arg(long, env = "MIXER_DATA_DIR", default_value = ".mixer", global = true)]
+    data_dir: PathBuf,
+    #[command(flatten)]
+    prover: ProverConfig,
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Create notes to deposit.
+    Note {
+        #[command(subcommand)]
+        command: NoteCommand,
+    },
+    /// Deposit notes that haven't been deposited yet.
+    Deposit {
+        /// Deposit at most this many notes.
+        #[arg(long)]
+        count: Option<usize>,
+        /// Queue the notes for `insert-batch` instead of inserting them.
+        #[arg(long)]
+        queue: bool,
+    },
+    /// Prove the program's queued deposits into its trees and insert them.
+    InsertBatch {
+        /// Insert at most this many queued notes.
+        #[arg(long)]
+        count: Option<usize>,
+    },
+    /// Fetch new leaves and refresh the leaf index and spent state of every note.
+    Sync,
+    /// Prove ownership of unspent notes and save the receipt bundle to a file.
+    Prove {
+        /// Commitment or nullifier hex prefixes; all unspent notes if omitted.
+        notes: Vec<String>,
+        /// Use fixed-depth paths instead of a multiproof.
+        #[arg(long)]
+        fixed: bool,
+        /// Prove each note on its own and aggregate the receipts into one.
+        #[arg(long)]
+        aggregate: bool,
+        #[arg(long, short, required_unless_present = "witness")]
+        out: Option<PathBuf>,
+        /// Instead, rerun the guest without proving on a witness saved by the
+        /// desktop app, or a JSON `WithdrawInput`, and report why it fails.
+        #[arg(long, conflicts_with_all = ["notes", "fixed", "aggregate", "out"])]
+        witness: Option<PathBuf>,
+        /// Password of the app that encrypted the witness.
+        #[arg(long, env = "MIXER_WITNESS_PASSWORD", hide_env_values = true)]
+        witness_password: Option<String>,
+    },
+    /// Verify a receipt bundle offline and print it.
+    Verify { bundle: PathBuf },
+    /// Print a receipt bundle without verifying it.
+    Inspect { bundle: PathBuf },
+    /// Submit a receipt bundle to the program it was made for.
+    Withdraw { bundle: PathBuf },
+    /// Prove ownership of a deposited note without spending it.
+    Attest {
+        /// Commitment or nullifier hex prefix of the note.
+        note: String,
+        /// Hex nonce chosen by whoever asks for the attestation.
+        #[arg(long)]
+        challenge: String,
+        /// Reveal the note's leaf index and commitment.
+        #[arg(long)]
+        disclose: bool,
+        /// Use a fixed-depth path instead of a multiproof.
+        #[arg(long)]
+        fixed: bool,
+        /// JSON array of flagged leaf indices the note must not be among.
+        #[arg(long)]
+        blocklist: Option<PathBuf>,
+        /// Prove against the blocklist's sparse Merkle root instead of the whole list.
+        #[arg(long, requires = "blocklist")]
+        sparse: bool,
+        #[arg(long, short)]
+        out: PathBuf,
+    },
+    /// Verify an ownership attestation offline and print it.
+    CheckAttestation {
+        attestation: PathBuf,
+        /// Fail unless the attestation answers this challenge.
+        #[arg(long)]
+        challenge: Option<String>,
+        /// Fail unless the attestation excludes the leaf indices in this JSON array.
+        #[arg(long)]
+        blocklist: Option<PathBuf>,
+        #[arg(long, requires = "blocklist")]
+        sparse: bool,
+        /// Fail unless the attestation is against this hex root, instead of
+        /// asking the program it names whether it recorded the root.
+        #[arg(long)]
+        root: Option<String>,
+    },
+    /// Inspect stored notes.
+    Notes {
+        #[command(subcommand)]
+        command: NotesCommand,
+    },
+}
+
+#[derive(Subcommand)]
+enum NoteCommand {
+    New {
+        #[arg(long, default_value_t = 1)]
+        count: usize,
+    },
+}
 
-#[derive(Clone)]
-struct DigestWrapper(Impl);
+#[derive(Subcommand)]
+enum NotesCommand {
+    List,
+    /// Print notes, secrets included, as JSON.
+    Export {
+        /// Commitment or nullifier hex prefixes; all notes if omitted.
+        notes: Vec<String>,
+        #[arg(long, short)]
+        out: Option<PathBuf>,
+    },
+}
 
-impl rs_merkle::Hasher for DigestWrapper{
-    type Hash = [u8; 32];
-    fn hash(data: &[u8]) -> Self::Hash {
-        (*Impl::hash_bytes(data)).into()
+impl Cli {
+    fn program_id(&self) -> Result<ActorId, Box<dyn Error>> {
+        let program = self.program.as_deref().ok_or("Program id is not set, use --program or MIXER_PROGRAM")?;
+        let program: [u8; 32] = hex::decode(program.trim_start_matches("0x"))?
+            .try_into()
+            .map_err(|_| "Program id must be 32 bytes")?;
+        Ok(ActorId::from(program))
     }
+
+    async fn client(&self) -> Result<MixerClient, Box<dyn Error>> {
+        self.client_for(self.program_id()?).await
+    }
+
+    async fn client_for(&self, program: ActorId) -> Result<MixerClient, Box<dyn Error>> {
+        let api = GearApi::init_with(WSAddress::try_new(&self.node, self.port)?, &self.suri).await?;
+        Ok(MixerClient::new(api, program))
+    }
+}
+
+fn parse_hash(hash: &str) -> Result<[u8; 32], Box<dyn Error>> {
+    Ok(hex::decode(hash.trim_start_matches("0x"))?
+        .try_into()
+        .map_err(|_| "Expected 32 hex-encoded bytes")?)
 }
 
-fn deserialize_public_outputs(bytes: Vec<u8>) -> PublicOutputs{
-    let bytes_len = bytes.len();
-    assert!(bytes_len >= 132, "Wrong public outputs");
-    let mut root = [0u8; 32];
+fn read_blocklist(path: &Path) -> Result<Vec<u64>, Box<dyn Error>> {
+    Ok(serde_json::from_slice(&fs::read(path)?)?)
+}
 
-    let mut chunks = bytes.chunks_exact(4);
-    for i in 0..32{
-        root[i] = chunks.next().unwrap()[0];
+fn read_bundle(path: &Path) -> Result<ReceiptBundle, Box<dyn Error>> {
+    Ok(ReceiptBundle::from_bytes(&fs::read(path)?)?)
+}
+
+fn print_root(root: [u8; 32], tree: MembershipTree) {
+    match tree {
+        MembershipTree::AppendOnly { leaves } => println!("root {} of {leaves} leaves", hex::encode(root)),
+        MembershipTree::Fixed => println!("root {} of the fixed tree", hex::encode(root)),
     }
-    let len = u32::from_le_bytes(chunks.next().unwrap().try_into().unwrap()) as usize;
-    assert_eq!(bytes_len, 132 + len * 32 * 4, "Wrong public outputs");
-
-    let mut used = Vec::with_capacity(len);
-    for _ in 0..len{
-        let mut elem = [0u8; 32];
-        for i in 0..32{
-            elem[i] = chunks.next().unwrap()[0];
-        }
-        used.push(elem);
+}
+
+fn print_bundle(bundle: &ReceiptBundle, outputs: &PublicOutputs) {
+    let created_at = SystemTime::UNIX_EPOCH + Duration::from_secs(bundle.created_at);
+    let image_id: Vec<u8> = bundle.image_id.iter().flat_map(|word| word.to_le_bytes()).collect();
+    println!("contract {}", hex::encode(<[u8; 32]>::from(bundle.contract)));
+    println!("created {}", humantime::format_rfc3339_seconds(created_at));
+    println!("image id {}", hex::encode(image_id));
+    println!("receipt {:?}, {} bytes", bundle.kind, bundle.image_id_receipt.len());
+    print_root(outputs.root, outputs.tree);
+    outputs.used.iter().for_each(|nullifier| println!("nullifier {}", hex::encode(nullifier)));
+    if let Some(call) = outputs.call {
+        println!("call {}", hex::encode(call));
+    }
+    for output in outputs.outputs.iter() {
+        println!("output {} x{}", hex::encode(output.recipient), output.note_count);
     }
-    PublicOutputs{root, used}
 }
 
-fn main(){
-    let elems: Vec<RustShit> = (0..10).map(|x: u32| RustShit([x as u8; 32], [x as u8; 32])).collect();
-    let leaves: Vec<[u8; 32]> = elems
-        .iter()
-        .map(|x| {
-            let a: [u8; 64] = unsafe{std::mem::transmute(*x)};
-            DigestWrapper::hash(&a)
-        })
-        .collect();
-
-    let merkle_tree = MerkleTree::<DigestWrapper>::from_leaves(&leaves);
-    let indices_to_prove: Vec<usize> = (6..8).into_iter().collect();
-    let elems_to_prove = elems.get(6..8).ok_or("can't get leaves to prove").unwrap().to_vec();
-    let merkle_proof = merkle_tree.proof(&indices_to_prove);
-    let merkle_root = merkle_tree.root().ok_or("couldn't get the merkle root").unwrap();
-    let proof_bytes = merkle_proof.to_bytes();
-
-    let env = ExecutorEnv::builder()
-        .write(&proof_bytes)
-        .unwrap()
-        .write(&indices_to_prove)
-        .unwrap()
-        .write(&elems_to_prove)
-        .unwrap()
-        .write(&leaves.len())
-        .unwrap()
-        .build()
-        .unwrap();
-
-    let prover = default_prover();
-
-    let start_time = Instant::now();
-    let receipt = prover
-        .prove(env, HELLO_GUEST_ELF)
-        .unwrap();
-    let duration = start_time.elapsed();
-    let time_for_proof = duration.as_millis(); 
-
-    // let output: PublicOutputs = receipt.receipt.journal.decode().unwrap();
-    let output = deserialize_public_outputs(receipt.receipt.journal.bytes.clone());
-
-    let start_time = Instant::now();
-    let a = receipt.receipt.verify(HELLO_GUEST_ID);
-    let duration = start_time.elapsed();
-    let time_for_verify = duration.as_millis(); 
-
-    match a {
-        Ok(..) => {
-            let real_output = merkle_root;
-            if output.root == real_output {
-                println!("time for creating proof {} ms\ntime for verify {} ms", time_for_proof, time_for_verify);
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let cli = Cli::parse();
+    let mut store = Store::open(&cli.data_dir)?;
+
+    match &cli.command {
+        Command::Note { command: NoteCommand::New { count } } => {
+            for _ in 0..*count {
+                let note = Note::random();
+                println!("{}", hex::encode(note.commitment));
+                store.notes.push(note);
+            }
+            store.save()?;
+        }
+        Command::Deposit { count, queue } => {
+            let pending: Vec<usize> = (0..store.notes.len())
+                .filter(|&i| !store.notes[i].deposited)
+                .take(count.unwrap_or(usize::MAX))
+                .collect();
+            if pending.is_empty() {
+                Err("No new notes, create some with `note new`")?;
+            }
+            let commitments = pending.iter().map(|&i| store.notes[i].commitment).collect();
+            let client = cli.client().await?;
+            if *queue {
+                client.enqueue(commitments).await?;
+            } else {
+                client.deposit(commitments).await?;
+            }
+            pending.iter().for_each(|&i| store.notes[i].deposited = true);
+            store.save()?;
+            println!("Deposited {} notes", pending.len());
+        }
+        Command::InsertBatch { count } => {
+            let client = cli.client().await?;
+            let mut queue = client.queue().await?;
+            queue.truncate(count.unwrap_or(usize::MAX));
+            if queue.is_empty() {
+                Err("No queued deposits")?;
+            }
+            let (frontier, fixed_tree) = (client.frontier().await?, client.fixed_tree().await?);
+            let image_id_receipt = prove::batch(&frontier, &fixed_tree, &queue, &cli.prover)?;
+            client.insert_batch(image_id_receipt).await?;
+            println!("Inserted {} queued notes", queue.len());
+        }
+        Command::Sync => {
+            let client = cli.client().await?;
+            let mut leaves = store.leaves()?;
+            loop {
+                let page = client.leaves_page(leaves.len() as u64, LEAVES_PAGE).await?;
+                let done = page.len() < LEAVES_PAGE as usize;
+                leaves.extend(page);
+                if done {
+                    break;
+                }
+            }
+            store.save_leaves(&leaves)?;
+
+            // The program indexes its leaves, so notes are looked up there instead of in `leaves`.
+            let unindexed: Vec<usize> = (0..store.notes.len()).filter(|&i| store.notes[i].leaf_index.is_none()).collect();
+            let commitments = unindexed.iter().map(|&i| store.notes[i].commitment).collect();
+            let indices = client.leaf_index_of(commitments).await?;
+            for (i, index) in unindexed.into_iter().zip(indices) {
+                store.notes[i].leaf_index = index;
             }
-            else{
-                println!("output_hash = {:?}, real_hash = {:?}", output.root, real_output)
+
+            let nullifiers = store.notes.iter().map(|note| note.nullifier).collect();
+            let spent = client.is_spent(nullifiers).await?;
+            for (note, spent) in store.notes.iter_mut().zip(spent) {
+                note.deposited |= note.leaf_index.is_some();
+                note.spent = spent;
+            }
+            store.save()?;
+            println!("Synced {} leaves", leaves.len());
+        }
+        Command::Prove {
+            witness: Some(witness),
+            witness_password,
+            ..
+        } => {
+            let input = witness::read(witness, witness_password.as_deref())?;
+            let (outputs, cycles) = witness::execute(&input)?;
+            print_root(outputs.root, outputs.tree);
+            outputs.used.iter().for_each(|nullifier| println!("nullifier {}", hex::encode(nullifier)));
+            println!("Guest succeeded in {cycles} cycles");
+        }
+        Command::Prove {
+            notes,
+            fixed,
+            aggregate,
+            out,
+            ..
+        } => {
+            let out = out.as_ref().ok_or("--out is required")?;
+            let leaves = store.leaves()?;
+            let selected: Vec<(u64, _)> = store
+                .select(notes)
+                .into_iter()
+                .map(|i| &store.notes[i])
+                .filter(|note| !note.spent)
+                .filter_map(|note| Some((note.leaf_index?, note.secret_note())))
+                .collect();
+            if selected.is_empty() {
+                Err("No unspent notes in the pool, run `sync` first")?;
             }
+            let count = selected.len();
+            let bundle = prove::prove(selected, &leaves, *fixed, *aggregate, cli.program_id()?, &cli.prover)?;
+            fs::write(out, bundle.to_bytes())?;
+            println!("Proved {count} notes into {}", out.display());
         }
-        _ => println!("Wrong proof")
-    };
-}
\ No newline at end of file
+        Command::Verify { bundle } => {
+            let bundle = read_bundle(bundle)?;
+            let outputs = prove::verify(&bundle)?;
+            print_bundle(&bundle, &outputs);
+            println!("Receipt is valid");
+        }
+        Command::Inspect { bundle } => {
+            let bundle = read_bundle(bundle)?;
+            print_bundle(&bundle, &bundle.outputs()?);
+        }
+        Command::Withdraw { bundle } => {
+            let bundle = read_bundle(bundle)?;
+            let outputs = prove::verify(&bundle)?;
+            if bundle.contract != cli.program_id()? {
+                Err("Receipt was made for another program")?;
+            }
+            let value = cli.client().await?.withdraw(bundle.image_id_receipt).await?;
+            store
+                .notes
+                .iter_mut()
+                .filter(|note| outputs.used.contains(&note.nullifier))
+                .for_each(|note| note.spent = true);
+            store.save()?;
+            println!("Withdrawn {value}");
+        }
+        Command::Attest {
+            note,
+            challenge,
+            disclose,
+            fixed,
+            blocklist,
+            sparse,
+            out,
+        } => {
+            let leaves = store.leaves()?;
+            let note = match store.select(std::slice::from_ref(note))[..] {
+                [i] => &store.notes[i],
+                [] => Err("No such note")?,
+                _ => Err("Prefix matches several notes")?,
+            };
+            let index = note.leaf_index.ok_or("Note is not in the pool, run `sync` first")?;
+            let blocklist = match blocklist {
+                Some(path) => Some(prove::blocklist(read_blocklist(path)?, *sparse, index)?),
+                None => None,
+            };
+            let input = prove::ownership_input(
+                (index, note.secret_note()),
+                &leaves,
+                *fixed,
+                parse_hash(challenge)?,
+                *disclose,
+                blocklist,
+            )?;
+            let bundle = prove::attest(&input, cli.program_id()?, &cli.prover)?;
+            fs::write(out, bundle.to_bytes())?;
+            println!("Attested ownership into {}", out.display());
+        }
+        Command::CheckAttestation {
+            attestation,
+            challenge,
+            blocklist,
+            sparse,
+            root,
+        } => {
+            let bundle = read_bundle(attestation)?;
+            let challenge = challenge.as_deref().map(parse_hash).transpose()?;
+            let blocklist = match blocklist {
+                Some(path) => Some(prove::blocklist(read_blocklist(path)?, *sparse, 0)?.commitment::<MerkleHasher>()),
+                None => None,
+            };
+            let outputs = prove::verify_attestation(&bundle, challenge, blocklist)?;
+            match root {
+                Some(root) if parse_hash(root)? != outputs.root => Err("Attestation is against another root")?,
+                Some(_) => {}
+                None => {
+                    let client = cli.client_for(bundle.contract).await?;
+                    if !client.is_known_root(outputs.root, outputs.tree).await? {
+                        Err("Attestation is against a root its program never recorded")?;
+                    }
+                }
+            }
+            let created_at = SystemTime::UNIX_EPOCH + Duration::from_secs(bundle.created_at);
+            println!("contract {}", hex::encode(<[u8; 32]>::from(bundle.contract)));
+            println!("created {}", humantime::format_rfc3339_seconds(created_at));
+            print_root(outputs.root, outputs.tree);
+            println!("challenge {}", hex::encode(outputs.challenge));
+            if let Some(leaf) = outputs.leaf {
+                println!("leaf {} {}", leaf.index, hex::encode(leaf.commitment));
+            }
+            match outputs.blocklist {
+                Some(BlocklistCommitment::Sorted(hash)) => println!("excluded from list {}", hex::encode(hash)),
+                Some(BlocklistCommitment::Sparse(root)) => println!("excluded from tree {}", hex::encode(root)),
+                None => {}
+            }
+            println!("Attestation is valid");
+        }
+        Command::Notes { command: NotesCommand::List } => {
+            for note in store.notes.iter() {
+                let leaf_index = note.leaf_index.map(|i| i.to_string()).unwrap_or_else(|| "-".into());
+                println!("{} {:>8} {}", hex::encode(note.commitment), leaf_index, note.status());
+            }
+        }
+        Command::Notes { command: NotesCommand::Export { notes, out } } => {
+            let selected: Vec<&Note> = store.select(notes).into_iter().map(|i| &store.notes[i]).collect();
+            let json = serde_json::to_string_pretty(&selected)?;
+            match out {
+                Some(out) => fs::write(out, json)?,
+                None => println!("{json}"),
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,101 @@
+use std::{error::Error, fs, path::{Path, PathBuf}};
+
+use mixer_protocol::{rs_merkle::Hasher, MerkleHasher, RustShit};
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Note {
+    #[serde(with = "hex::serde")]
+    pub nullifier: [u8; 32],
+    #[serde(with = "hex::serde")]
+    pub secret: [u8; 32],
+    #[serde(with = "hex::serde")]
+    pub commitment: [u8; 32],
+    pub deposited: bool,
+    pub leaf_index: Option<u64>,
+    pub spent: bool,
+}
+
+impl Note {
+    pub fn random() -> Self {
+        let note = RustShit(rand::random(), rand::random());
+        Self {
+            nullifier: note.0,
+            secret: note.1,
+            commitment: MerkleHasher::hash(&note.to_preimage()),
+            deposited: false,
+            leaf_index: None,
+            spent: false,
+        }
+    }
+
+    pub fn secret_note(&self) -> RustShit {
+        RustShit(self.nullifier, self.secret)
+    }
+
+    pub fn status(&self) -> &'static str {
+        match (self.deposited, self.leaf_index, self.spent) {
+            (_, _, true) => "spent",
+            (_, Some(_), false) => "in pool",
+            (true, None, false) => "deposited",
+            (false, None, false) => "new",
+        }
+    }
+}
+
+/// Notes and the synced copy of the pool's leaves, kept in the data directory.
+pub struct Store {
+    dir: PathBuf,
+    pub notes: Vec<Note>,
+}
+
+impl Store {
+    pub fn open(dir: &Path) -> Result<Self, Box<dyn Error>> {
+        fs::create_dir_all(dir)?;
+        let notes_path = dir.join("notes.json");
+        let notes = match fs::read(&notes_path) {
+            Ok(bytes) => serde_json::from_slice(&bytes)?,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => Err(e)?,
+        };
+        Ok(Self { dir: dir.to_path_buf(), notes })
+    }
+
+    pub fn save(&self) -> Result<(), Box<dyn Error>> {
+        let tmp = self.dir.join("notes.json.tmp");
+        fs::write(&tmp, serde_json::to_vec_pretty(&self.notes)?)?;
+        fs::rename(tmp, self.dir.join("notes.json"))?;
+        Ok(())
+    }
+
+    pub fn leaves(&self) -> Result<Vec<[u8; 32]>, Box<dyn Error>> {
+        let bytes = match fs::read(self.dir.join("leaves.bin")) {
+            Ok(bytes) => bytes,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => Err(e)?,
+        };
+        if bytes.len() % 32 != 0 {
+            Err("Leaves cache is corrupted, remove leaves.bin and sync again")?;
+        }
+        Ok(bytes.chunks(32).map(|leaf| leaf.try_into().unwrap()).collect())
+    }
+
+    pub fn save_leaves(&self, leaves: &[[u8; 32]]) -> Result<(), Box<dyn Error>> {
+        fs::write(self.dir.join("leaves.bin"), leaves.concat())?;
+        Ok(())
+    }
+
+    /// Notes whose commitment or nullifier starts with one of `prefixes`
+    /// (hex), or every note when `prefixes` is empty.
+    pub fn select(&self, prefixes: &[String]) -> Vec<usize> {
+        (0..self.notes.len())
+            .filter(|&i| {
+                let note = &self.notes[i];
+                prefixes.is_empty()
+                    || prefixes.iter().any(|prefix| {
+                        hex::encode(note.commitment).starts_with(prefix) || hex::encode(note.nullifier).starts_with(prefix)
+                    })
+            })
+            .collect()
+    }
+}
@@ -0,0 +1,233 @@
+use std::{error::Error, time::SystemTime};
+
+use methods::{
+    AGGREGATE_GUEST_ELF, AGGREGATE_GUEST_ID, BATCH_GUEST_ELF, BATCH_GUEST_ID, HELLO_GUEST_ELF, HELLO_GUEST_ID, OWNERSHIP_GUEST_ELF,
+    OWNERSHIP_GUEST_ID,
+};
+use mixer_backend::{ProverConfig, ReceiptFormat};
+use mixer_protocol::{
+    rs_merkle::MerkleTree, ActorId, AggregateInput, AggregateOutputs, Blocklist, BlocklistCommitment, FixedTree, Frontier, Membership,
+    MerkleHasher, MerklePath, OwnershipInput, OwnershipOutputs, PublicOutputs, ReceiptBundle, ReceiptKind, RustShit,
+    SparseBlocklist, WithdrawInput,
+};
+use risc0_zkvm::{InnerReceipt, Receipt};
+
+/// Proves that `notes` (leaf index and secret) are in `leaves` and bundles the
+/// receipt for withdrawing from `contract`. With `aggregate` each note is
+/// proved on its own and the receipts are folded into one.
+pub fn prove(
+    mut notes: Vec<(u64, RustShit)>,
+    leaves: &[[u8; 32]],
+    fixed: bool,
+    aggregate: bool,
+    contract: ActorId,
+    config: &ProverConfig,
+) -> Result<ReceiptBundle, Box<dyn Error>> {
+    notes.sort_by_key(|(index, _)| *index);
+    if aggregate {
+        return prove_aggregate(notes, leaves, fixed, contract, config);
+    }
+    let input = withdraw_input(notes, leaves, fixed)?;
+    let env = config.env().write(&input)?.build()?;
+    let receipt = config.prove(env, HELLO_GUEST_ELF)?;
+    bundle(HELLO_GUEST_ID, receipt, contract)
+}
+
+fn prove_aggregate(
+    notes: Vec<(u64, RustShit)>,
+    leaves: &[[u8; 32]],
+    fixed: bool,
+    contract: ActorId,
+    config: &ProverConfig,
+) -> Result<ReceiptBundle, Box<dyn Error>> {
+    // A composite receipt keeps the note receipts as unresolved assumptions.
+    if config.receipt == ReceiptFormat::Composite {
+        Err("Aggregate receipts can't be composite, use --receipt succinct or groth16")?;
+    }
+    // Only succinct receipts resolve as assumptions, whatever the aggregate is wrapped in.
+    let note_config = ProverConfig {
+        receipt: ReceiptFormat::Succinct,
+        ..config.clone()
+    };
+
+    let mut env = config.env();
+    let mut journals = Vec::with_capacity(notes.len());
+    for note in notes {
+        let input = withdraw_input(vec![note], leaves, fixed)?;
+        let receipt = note_config.prove(note_config.env().write(&input)?.build()?, HELLO_GUEST_ELF)?;
+        journals.push(receipt.journal.bytes.clone());
+        env.add_assumption(receipt);
+    }
+    let input = AggregateInput {
+        note_image_id: HELLO_GUEST_ID,
+        journals,
+    };
+    let receipt = config.prove(env.write(&input)?.build()?, AGGREGATE_GUEST_ELF)?;
+    bundle(AGGREGATE_GUEST_ID, receipt, contract)
+}
+
+/// Withdraw guest input for `notes`, which must be sorted by leaf index.
+fn withdraw_input(
+    notes: Vec<(u64, RustShit)>,
+    leaves: &[[u8; 32]],
+    fixed: bool,
+) -> Result<WithdrawInput, Box<dyn Error>> {
+    let (indices, notes): (Vec<u64>, Vec<RustShit>) = notes.into_iter().unzip();
+    let input = match membership(&indices, leaves, fixed) {
+        Membership::Paths(paths) => WithdrawInput::with_paths(paths, notes),
+        Membership::MultiProof {
+            proof_bytes,
+            indices,
+            total_leaves_count,
+        } => WithdrawInput::new(proof_bytes, indices, notes, total_leaves_count),
+    };
+    input.validate()?;
+    Ok(input)
+}
+
+/// Proves appending `commitments` to the contract's `frontier` and
+/// `fixed_tree`, and returns the receipt `InsertBatch` takes.
+pub fn batch(
+    frontier: &Frontier,
+    fixed_tree: &FixedTree,
+    commitments: &[[u8; 32]],
+    config: &ProverConfig,
+) -> Result<Vec<u8>, Box<dyn Error>> {
+    if !frontier.is_well_formed() {
+        Err("Frontier doesn't match its length")?;
+    }
+    let env = config.env().write(frontier)?.write(fixed_tree)?.write(&commitments.to_vec())?.build()?;
+    let receipt = config.prove(env, BATCH_GUEST_ELF)?;
+    Ok(postcard::to_allocvec(&(BATCH_GUEST_ID, receipt))?)
+}
+
+/// The statement that `note` is in `leaves`, for an auditor's `challenge`,
+/// and that its index is not flagged by `blocklist` if one is given.
+pub fn ownership_input(
+    (index, note): (u64, RustShit),
+    leaves: &[[u8; 32]],
+    fixed: bool,
+    challenge: [u8; 32],
+    disclose: bool,
+    blocklist: Option<Blocklist>,
+) -> Result<OwnershipInput, Box<dyn Error>> {
+    let mut input = OwnershipInput::new(membership(&[index], leaves, fixed), note, challenge, disclose);
+    if let Some(blocklist) = blocklist {
+        if !blocklist.excludes::<MerkleHasher>(index) {
+            Err("Note is on the blocklist")?;
+        }
+        input = input.excluding(blocklist);
+    }
+    input.validate()?;
+    Ok(input)
+}
+
+/// Proves `input` without spending its note and bundles the receipt for `contract`.
+pub fn attest(
+    input: &OwnershipInput,
+    contract: ActorId,
+    config: &ProverConfig,
+) -> Result<ReceiptBundle, Box<dyn Error>> {
+    let env = config.env().write(input)?.build()?;
+    let receipt = config.prove(env, OWNERSHIP_GUEST_ELF)?;
+    bundle(OWNERSHIP_GUEST_ID, receipt, contract)
+}
+
+/// The guest's view of the `flagged` leaf indices for proving `index` isn't
+/// among them: the whole sorted list, or a path into their sparse tree.
+pub fn blocklist(mut flagged: Vec<u64>, sparse: bool, index: u64) -> Result<Blocklist, Box<dyn Error>> {
+    flagged.sort_unstable();
+    flagged.dedup();
+    Ok(if sparse {
+        let tree = SparseBlocklist::new::<MerkleHasher>(&flagged)?;
+        Blocklist::Sparse {
+            root: tree.root(),
+            path: tree.path(index)?,
+        }
+    } else {
+        Blocklist::Sorted(flagged)
+    })
+}
+
+fn membership(indices: &[u64], leaves: &[[u8; 32]], fixed: bool) -> Membership {
+    if fixed {
+        Membership::Paths(indices.iter().map(|&index| MerklePath::build::<MerkleHasher>(leaves, index)).collect())
+    } else {
+        let proof_indices: Vec<usize> = indices.iter().map(|&index| index as usize).collect();
+        Membership::MultiProof {
+            proof_bytes: MerkleTree::<MerkleHasher>::from_leaves(leaves).proof(&proof_indices).to_bytes(),
+            indices: indices.to_vec(),
+            total_leaves_count: leaves.len() as u64,
+        }
+    }
+}
+
+fn kind(receipt: &Receipt) -> Result<ReceiptKind, Box<dyn Error>> {
+    Ok(match receipt.inner {
+        InnerReceipt::Composite(_) => ReceiptKind::Composite,
+        InnerReceipt::Succinct(_) => ReceiptKind::Succinct,
+        InnerReceipt::Groth16(_) => ReceiptKind::Groth16,
+        InnerReceipt::Fake(_) => ReceiptKind::Fake,
+        _ => Err("Unknown receipt kind")?,
+    })
+}
+
+fn bundle(image_id: [u32; 8], receipt: Receipt, contract: ActorId) -> Result<ReceiptBundle, Box<dyn Error>> {
+    Ok(ReceiptBundle {
+        image_id,
+        kind: kind(&receipt)?,
+        contract,
+        created_at: SystemTime::now().duration_since(SystemTime::UNIX_EPOCH)?.as_secs(),
+        journal: receipt.journal.bytes.clone(),
+        image_id_receipt: postcard::to_allocvec(&(image_id, receipt))?,
+    })
+}
+
+/// Checks a bundle's receipt the way the contract does, short of the root and
+/// nullifier lookups, and that the rest of the bundle agrees with it.
+pub fn verify(bundle: &ReceiptBundle) -> Result<PublicOutputs, Box<dyn Error>> {
+    let receipt = verified_receipt(bundle)?;
+    let image_id = bundle.image_id;
+
+    if image_id == HELLO_GUEST_ID {
+        Ok(PublicOutputs::from_journal(&receipt.journal.bytes)?)
+    } else if image_id == AGGREGATE_GUEST_ID {
+        let aggregate = AggregateOutputs::from_journal(&receipt.journal.bytes)?;
+        if aggregate.note_image_id != HELLO_GUEST_ID {
+            Err("Aggregate receipt is built from unknown note proofs")?;
+        }
+        Ok(aggregate.withdraw)
+    } else {
+        Err("Receipt is for an unknown guest")?
+    }
+}
+
+/// Checks an ownership attestation and, if given, that it answers `challenge`
+/// and excludes the notes of `blocklist`. Whether the program recorded its
+/// root takes a node, so that is left to the caller.
+pub fn verify_attestation(
+    bundle: &ReceiptBundle,
+    challenge: Option<[u8; 32]>,
+    blocklist: Option<BlocklistCommitment>,
+) -> Result<OwnershipOutputs, Box<dyn Error>> {
+    if bundle.image_id != OWNERSHIP_GUEST_ID {
+        Err("Not an ownership attestation")?;
+    }
+    let outputs = OwnershipOutputs::from_journal(&verified_receipt(bundle)?.journal.bytes)?;
+    if challenge.is_some_and(|challenge| challenge != outputs.challenge) {
+        Err("Attestation answers another challenge")?;
+    }
+    if blocklist.is_some() && blocklist != outputs.blocklist {
+        Err("Attestation was made against another blocklist")?;
+    }
+    Ok(outputs)
+}
+
+fn verified_receipt(bundle: &ReceiptBundle) -> Result<Receipt, Box<dyn Error>> {
+    let (image_id, receipt): ([u32; 8], Receipt) = postcard::from_bytes(&bundle.image_id_receipt)?;
+    if image_id != bundle.image_id || kind(&receipt)? != bundle.kind || receipt.journal.bytes != bundle.journal {
+        Err("Bundle does not match its receipt")?;
+    }
+    receipt.verify(image_id)?;
+    Ok(receipt)
+}
@@ -0,0 +1,57 @@
+use std::{error::Error, fs, path::Path};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use crypto_secretbox::{
+    aead::{Aead, KeyInit},
+    Nonce, XSalsa20Poly1305,
+};
+use methods::HELLO_GUEST_ELF;
+use mixer_protocol::{PublicOutputs, WithdrawInput};
+use risc0_zkvm::{default_executor, ExecutorEnv};
+use scrypt::{scrypt, Params};
+
+// The desktop app encrypts witnesses like exported notes: base64 of
+// `salt ++ nonce ++ secretbox(JSON input)`, keyed with scrypt of its password.
+const SALT_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 24;
+const SCRYPT_LOG_N: u8 = 15;
+const SCRYPT_R: u32 = 8;
+const SCRYPT_P: u32 = 1;
+
+/// Reads a witness the app saved, or a plain JSON `WithdrawInput`.
+pub fn read(path: &Path, password: Option<&str>) -> Result<WithdrawInput, Box<dyn Error>> {
+    let contents = fs::read_to_string(path)?;
+    if contents.trim_start().starts_with('{') {
+        return Ok(serde_json::from_str(&contents)?);
+    }
+
+    let password = password.ok_or("The witness is encrypted, pass the app's password with --witness-password")?;
+    let encrypted = STANDARD.decode(contents.trim())?;
+    if encrypted.len() < SALT_LENGTH + NONCE_LENGTH {
+        Err("Witness is too short")?;
+    }
+    let (salt, rest) = encrypted.split_at(SALT_LENGTH);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LENGTH);
+
+    let mut key = [0; 32];
+    // scrypt's errors only implement `Error` with its `std` feature.
+    let params = Params::new(SCRYPT_LOG_N, SCRYPT_R, SCRYPT_P, key.len()).map_err(|e| e.to_string())?;
+    scrypt(password.as_bytes(), salt, &params, &mut key).map_err(|e| e.to_string())?;
+    let json = XSalsa20Poly1305::new(&key.into())
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| "Wrong password or corrupted witness")?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
+/// Runs the withdraw guest on `input` without proving, so a failed withdrawal
+/// fails the same way here. Returns the journal and the cycle count.
+pub fn execute(input: &WithdrawInput) -> Result<(PublicOutputs, u64), Box<dyn Error>> {
+    // The guest panics on invalid input; this tells which check.
+    input.validate().map_err(|e| format!("Guest rejects the input: {e}"))?;
+
+    let env = ExecutorEnv::builder().write(input)?.build()?;
+    let session = default_executor()
+        .execute(env, HELLO_GUEST_ELF)
+        .map_err(|e| format!("Guest failed: {e}"))?;
+    Ok((PublicOutputs::from_journal(&session.journal.bytes)?, session.cycles()))
+}
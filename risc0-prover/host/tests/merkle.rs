@@ -0,0 +1,15 @@
+//! The guests hash with the same `MerkleHasher`; this pins risc0's SHA-256 to
+//! the shared vectors `mixer-protocol` checks against the `sha2` crate.
+
+use mixer_protocol::{test_vectors, MerkleHasher};
+
+#[test]
+fn merkle_vectors() {
+    test_vectors::check::<MerkleHasher>();
+}
+
+#[test]
+fn fixtures() {
+    let fixtures = serde_json::from_str(test_vectors::FIXTURES).unwrap();
+    test_vectors::check_fixtures::<MerkleHasher>(&fixtures);
+}
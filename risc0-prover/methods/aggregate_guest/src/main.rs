@@ -0,0 +1,34 @@
+#![no_main]
+
+risc0_zkvm::guest::entry!(main);
+
+use risc0_zkvm::guest::env;
+use mixer_protocol::{AggregateInput, AggregateOutputs, PublicOutputs};
+
+fn main(){
+    let AggregateInput{note_image_id, journals} = env::read();
+    assert!(!journals.is_empty(), "Nothing to aggregate");
+
+    let mut root_tree = None;
+    let mut used = Vec::with_capacity(journals.len());
+    let mut outputs = Vec::new();
+    for journal in journals.iter(){
+        env::verify(note_image_id, journal).unwrap();
+        let note = PublicOutputs::from_journal(journal).expect("Malformed note journal");
+
+        assert_eq!(note.used.len(), 1, "Each receipt must spend exactly one note");
+        assert!(note.call.is_none(), "Calls can't be aggregated");
+        let note_root_tree = (note.root, note.tree);
+        assert_eq!(*root_tree.get_or_insert(note_root_tree), note_root_tree, "Notes are proved against different roots");
+
+        used.extend(note.used);
+        outputs.extend(note.outputs);
+    }
+    assert!(outputs.is_empty() || outputs.len() == used.len(), "Either every note or none must name a recipient");
+
+    let (root, tree) = root_tree.unwrap();
+    let withdraw = PublicOutputs{root, tree, used, call: None, outputs};
+    let aggregate_outputs = AggregateOutputs{note_image_id, withdraw};
+
+    env::commit_slice(&aggregate_outputs.to_journal());
+}
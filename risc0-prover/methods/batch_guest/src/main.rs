@@ -0,0 +1,41 @@
+#![no_main]
+
+risc0_zkvm::guest::entry!(main);
+
+use risc0_zkvm::{
+    guest::env,
+    sha::{Impl, Sha256},
+};
+use mixer_protocol::{zero_hashes, BatchOutputs, FixedTree, Frontier, MerkleHasher};
+
+fn main(){
+    let mut frontier: Frontier = env::read();
+    let mut fixed_tree: FixedTree = env::read();
+    let commitments: Vec<[u8; 32]> = env::read();
+    assert!(frontier.is_well_formed(), "Frontier doesn't match its length");
+
+    let old_root = frontier.root::<MerkleHasher>();
+    let old_len = frontier.len;
+    let old_fixed = fixed_tree.commitment::<MerkleHasher>();
+
+    let zeros = zero_hashes::<MerkleHasher>();
+    for commitment in commitments.iter(){
+        frontier.append::<MerkleHasher>(*commitment);
+        fixed_tree.append::<MerkleHasher>(&zeros, *commitment);
+    }
+    let batch_hash = (*Impl::hash_bytes(&commitments.concat())).into();
+
+    let batch_outputs = BatchOutputs{
+        old_root,
+        old_len,
+        count: commitments.len() as u64,
+        batch_hash,
+        new_root: frontier.root::<MerkleHasher>(),
+        new_peaks: frontier.peaks,
+        old_fixed,
+        new_fixed_root: fixed_tree.root(&zeros),
+        new_filled: fixed_tree.filled,
+    };
+
+    env::commit_slice(&batch_outputs.to_journal());
+}
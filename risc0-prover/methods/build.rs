@@ -1,3 +1,93 @@
+use std::{collections::HashMap, env, fs, path::PathBuf};
+
+use risc0_build::{embed_methods_with_options, DockerOptions, GuestListEntry, GuestOptions};
+
+// Image ids the contract accepts, and the ELFs and ids the wallet proves with.
+const CONTRACT_IDS: &str = "mixer-contract/src/guest_ids.rs";
+const WALLET_METHODS: &str = "mixer-frontend/src-tauri/src/crypto/mixing_handling/methods.rs";
+
+const GENERATED: &str = "// Generated by `risc0-prover/methods/build.rs` with `MIXER_EXPORT_GUESTS=1`, do not edit.\n";
+const REPRODUCIBLE: &str = "// Built in risc0's docker image.\n";
+
+// `MIXER_PROFILE_GUESTS=1` builds the withdraw guest with its `profile`
+// feature for `bin/profile.rs`. Its image id is of no use to anyone else, so
+// such a build leaves the generated files alone.
+//
+// `MIXER_REPRODUCIBLE_BUILD=1` builds the guests in risc0's docker image, so
+// their image ids don't depend on the local toolchain. Those are the ids
+// released with the contract and the wallet: such a build fails when the
+// checked-in copies disagree, and `MIXER_EXPORT_GUESTS=1` rewrites them.
+// Local toolchains produce other ids, so a local build only warns.
 fn main() {
-    risc0_build::embed_methods();
+    println!("cargo:rerun-if-env-changed=MIXER_REPRODUCIBLE_BUILD");
+    println!("cargo:rerun-if-env-changed=MIXER_EXPORT_GUESTS");
+    println!("cargo:rerun-if-env-changed=MIXER_PROFILE_GUESTS");
+
+    let root = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("../..").canonicalize().unwrap();
+    let reproducible = env::var_os("MIXER_REPRODUCIBLE_BUILD").is_some();
+    let profile = env::var_os("MIXER_PROFILE_GUESTS").is_some();
+    let options = GuestOptions {
+        features: Vec::new(),
+        // The guests depend on `mixer-protocol`, so docker needs the whole repository.
+        use_docker: reproducible.then(|| DockerOptions { root_dir: Some(root.clone()) }),
+    };
+    let hello = GuestOptions {
+        features: if profile { vec!["profile".to_owned()] } else { Vec::new() },
+        ..options.clone()
+    };
+    let guests = embed_methods_with_options(HashMap::from([
+        ("hello_guest", hello),
+        ("batch_guest", options.clone()),
+        ("aggregate_guest", options.clone()),
+        ("ownership_guest", options),
+    ]));
+    if env::var_os("RISC0_SKIP_BUILD").is_some() || profile {
+        return;
+    }
+
+    let guest = |name: &str| guests.iter().find(|guest| guest.name == name).unwrap();
+    let header = GENERATED.to_owned() + REPRODUCIBLE;
+    let contract = contract_ids(guest("hello_guest"), guest("batch_guest"), guest("aggregate_guest"));
+    let wallet = wallet_methods(guest("hello_guest"), guest("aggregate_guest"), guest("ownership_guest"));
+    let generated = [(CONTRACT_IDS, header.clone() + &contract), (WALLET_METHODS, header + &wallet)];
+
+    for (path, contents) in generated {
+        let path = root.join(path);
+        println!("cargo:rerun-if-changed={}", path.display());
+        if fs::read_to_string(&path).ok().as_deref() == Some(contents.as_str()) {
+            continue;
+        }
+        if env::var_os("MIXER_EXPORT_GUESTS").is_some() {
+            assert!(reproducible, "Guests must be exported from a reproducible build, set MIXER_REPRODUCIBLE_BUILD=1");
+            fs::write(&path, contents).unwrap();
+        } else if reproducible {
+            panic!("{} disagrees with the guests, rebuild with MIXER_EXPORT_GUESTS=1 to update it", path.display());
+        } else {
+            println!(
+                "cargo:warning={} differs from this local guest build, whose receipts the released contract rejects",
+                path.display()
+            );
+        }
+    }
+}
+
+fn id(name: &str, guest: &GuestListEntry) -> String {
+    format!("pub const {name}: [u32; 8] = {:?};\n", guest.image_id)
+}
+
+fn contract_ids(hello: &GuestListEntry, batch: &GuestListEntry, aggregate: &GuestListEntry) -> String {
+    id("GUEST_ID", hello) + &id("BATCH_GUEST_ID", batch) + &id("AGGREGATE_GUEST_ID", aggregate)
+}
+
+fn elf(name: &str, guest: &GuestListEntry) -> String {
+    format!("pub const {name}: &[u8] = &{:?};\n", &guest.elf[..])
+}
+
+fn wallet_methods(hello: &GuestListEntry, aggregate: &GuestListEntry, ownership: &GuestListEntry) -> String {
+    elf("HELLO_GUEST_ELF", hello)
+        + &id("HELLO_GUEST_ID", hello)
+        + &elf("AGGREGATE_GUEST_ELF", aggregate)
+        + &id("AGGREGATE_GUEST_ID", aggregate)
+        + &elf("OWNERSHIP_GUEST_ELF", ownership)
+        + &id("OWNERSHIP_GUEST_ID", ownership)
 }
@@ -7,36 +7,69 @@ use risc0_zkvm::{
     sha::{Impl, Sha256},
 };
 use rs_merkle::{Hasher, MerkleProof};
-use shared::{RustShit, PublicOutputs};
+use mixer_protocol::{Membership, MerkleHasher, PublicOutputs, WithdrawInput};
 
-#[derive(Clone)]
-struct DigestWrapper(Impl);
+/// Cycle count at the end of each phase. Builds with the `profile` feature
+/// print them to stderr for `bin/profile.rs`; other builds record nothing.
+#[derive(Default)]
+struct Phases(#[cfg(feature = "profile")] Vec<(&'static str, u64)>);
 
-impl rs_merkle::Hasher for DigestWrapper{
-    type Hash = [u8; 32];
-    fn hash(data: &[u8]) -> Self::Hash {
-        (*Impl::hash_bytes(data)).into()
+impl Phases{
+    fn end(&mut self, _name: &'static str){
+        #[cfg(feature = "profile")]
+        self.0.push((_name, env::cycle_count()));
+    }
+
+    fn report(self){
+        #[cfg(feature = "profile")]
+        for (name, cycles) in self.0{
+            eprintln!("mixer-phase {name} {cycles}");
+        }
     }
 }
 
 fn main(){
-    let proof_bytes: Vec<u8> = env::read();
-    let indices_to_prove: Vec<usize> = env::read();
-    let elems_to_prove: Vec<RustShit> = env::read();
-    let total_leaves_count: usize = env::read();
+    let mut phases = Phases::default();
+    let input: WithdrawInput = env::read();
+    if let Err(e) = input.validate(){
+        panic!("Invalid withdraw input: {e}");
+    }
+    let WithdrawInput{membership, notes, call, outputs, ..} = input;
+    phases.end("input");
 
-    let proof = MerkleProof::<DigestWrapper>::try_from(proof_bytes).unwrap();
+    let leaves: Vec<[u8; 32]> = notes.iter().map(|x| MerkleHasher::hash(&x.to_preimage())).collect();
+    let used: Vec<[u8; 32]> = notes.iter().map(|x| x.0).collect();
+    phases.end("leaves");
 
-    let leaves_to_prove_and_used: (Vec<[u8; 32]>, Vec<[u8; 32]>) = elems_to_prove.into_iter().map(|x| {
-        let b = x.0;
-        let a: [u8; 64] = unsafe{std::mem::transmute(x)};
-        (DigestWrapper::hash(&a), b)
-    }).collect();
+    let tree = membership.tree();
+    let root = match membership{
+        Membership::MultiProof{proof_bytes, indices, total_leaves_count} => {
+            let proof = MerkleProof::<MerkleHasher>::try_from(proof_bytes).expect("Malformed merkle proof");
+            let indices_to_prove: Vec<usize> = indices.into_iter().map(|x| x as usize).collect();
+            proof.root(&indices_to_prove, &leaves, total_leaves_count as usize).expect("Merkle proof doesn't match the notes")
+        }
+        Membership::Paths(paths) => {
+            let root = paths[0].root::<MerkleHasher>(leaves[0]);
+            for (path, leaf) in paths.iter().zip(leaves.iter()).skip(1){
+                assert_eq!(path.root::<MerkleHasher>(*leaf), root, "Merkle paths lead to different roots");
+            }
+            root
+        }
+    };
+    phases.end("root");
 
-    let root = proof.root(&indices_to_prove, &leaves_to_prove_and_used.0, total_leaves_count).unwrap();
-    let used = leaves_to_prove_and_used.1;
+    let call = call.map(|call| (*Impl::hash_bytes(&call.preimage())).into());
+
+    if !outputs.is_empty(){
+        assert!(call.is_none(), "Withdrawal can't be both a call and a split");
+        assert!(outputs.iter().all(|x| x.note_count != 0), "Empty split output");
+        let note_count: u64 = outputs.iter().map(|x| x.note_count as u64).sum();
+        assert_eq!(note_count, used.len() as u64, "Split outputs don't cover spent notes");
+    }
 
-    let public_outputs = PublicOutputs{root, used};
+    let public_outputs = PublicOutputs{root, tree, used, call, outputs};
 
-    env::commit(&public_outputs);
+    env::commit_slice(&public_outputs.to_journal());
+    phases.end("journal");
+    phases.report();
 }
@@ -0,0 +1,42 @@
+#![no_main]
+
+risc0_zkvm::guest::entry!(main);
+
+use risc0_zkvm::guest::env;
+use rs_merkle::{Hasher, MerkleProof};
+use mixer_protocol::{DisclosedLeaf, Membership, MerkleHasher, OwnershipInput, OwnershipOutputs};
+
+fn main(){
+    let input: OwnershipInput = env::read();
+    if let Err(e) = input.validate(){
+        panic!("Invalid ownership input: {e}");
+    }
+    let index = input.membership.indices()[0];
+    let tree = input.membership.tree();
+    let OwnershipInput{membership, note, challenge, disclose, blocklist, ..} = input;
+
+    let leaf = MerkleHasher::hash(&note.to_preimage());
+
+    let root = match membership{
+        Membership::MultiProof{proof_bytes, total_leaves_count, ..} => {
+            let proof = MerkleProof::<MerkleHasher>::try_from(proof_bytes).expect("Malformed merkle proof");
+            proof.root(&[index as usize], &[leaf], total_leaves_count as usize).expect("Merkle proof doesn't match the note")
+        }
+        Membership::Paths(paths) => paths[0].root::<MerkleHasher>(leaf),
+    };
+
+    let blocklist = blocklist.map(|blocklist| {
+        assert!(blocklist.excludes::<MerkleHasher>(index), "Note is on the blocklist");
+        blocklist.commitment::<MerkleHasher>()
+    });
+
+    let ownership_outputs = OwnershipOutputs{
+        root,
+        tree,
+        challenge,
+        leaf: disclose.then_some(DisclosedLeaf{index, commitment: leaf}),
+        blocklist,
+    };
+
+    env::commit_slice(&ownership_outputs.to_journal());
+}
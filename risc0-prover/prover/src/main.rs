@@ -0,0 +1,177 @@
+//! Local proving daemon. Withdraw inputs are posted as JSON, queued and
+//! proved by a pool of workers; clients poll the job and fetch the receipt.
+//!
+//! - `POST /jobs` with a JSON [`WithdrawInput`] returns `{"id": ..}`.
+//! - `GET /jobs/<id>` returns the job's status.
+//! - `GET /jobs/<id>/receipt` returns the postcard-encoded `(image id, receipt)`
+//!   pair and drops the job.
+//! - `DELETE /jobs/<id>` drops a queued or finished job.
+//!
+//! Witnesses hold note secrets and whoever submits a receipt is paid, so every
+//! request needs `Authorization: Bearer <token>`, and the daemon only listens
+//! on other than loopback addresses over TLS.
+
+use std::{fs, io::Cursor, net::SocketAddr, path::PathBuf, process::ExitCode, sync::Arc, thread, time::Duration};
+
+use clap::Parser;
+use mixer_backend::ProverConfig;
+use mixer_protocol::WithdrawInput;
+use serde::Serialize;
+use tiny_http::{Header, Method, Request, Response, Server, SslConfig};
+
+use queue::{Queue, QueueError};
+
+mod queue;
+
+type HttpResponse = Response<Cursor<Vec<u8>>>;
+
+#[derive(Parser)]
+#[command(name = "mixer-prover", about = "Proving daemon for Mixer withdrawals")]
+struct Args {
+    #[arg(long, env = "MIXER_PROVER_LISTEN", default_value = "127.0.0.1:7878")]
+    listen: SocketAddr,
+    /// Token clients send as `Authorization: Bearer <token>`. A random one is
+    /// made and printed when unset.
+    #[arg(long, env = "MIXER_PROVER_TOKEN", hide_env_values = true)]
+    token: Option<String>,
+    /// PEM certificate chain to serve HTTPS with; required to listen beyond loopback.
+    #[arg(long, requires = "tls_key")]
+    tls_cert: Option<PathBuf>,
+    /// PEM private key of `--tls-cert`.
+    #[arg(long, requires = "tls_cert")]
+    tls_key: Option<PathBuf>,
+    /// Jobs proved at the same time. Each prover already uses every core, so
+    /// more than one mostly helps when segments are small.
+    #[arg(long, env = "MIXER_PROVER_WORKERS", default_value_t = 1)]
+    workers: usize,
+    /// Queued jobs beyond which new ones are refused.
+    #[arg(long, default_value_t = 64)]
+    max_queue: usize,
+    /// Seconds a finished job is kept when its receipt isn't fetched.
+    #[arg(long, default_value_t = 600, value_parser = clap::value_parser!(u64).range(1..))]
+    job_ttl: u64,
+    #[command(flatten)]
+    prover: ProverConfig,
+}
+
+fn main() -> ExitCode {
+    let args = Args::parse();
+    let tls = match (&args.tls_cert, &args.tls_key) {
+        (Some(cert), Some(key)) => match (fs::read(cert), fs::read(key)) {
+            (Ok(certificate), Ok(private_key)) => Some(SslConfig { certificate, private_key }),
+            (Err(e), _) | (_, Err(e)) => {
+                eprintln!("Failed to read the TLS certificate or key: {e}");
+                return ExitCode::FAILURE;
+            }
+        },
+        _ => None,
+    };
+    if tls.is_none() && !args.listen.ip().is_loopback() {
+        eprintln!("Witnesses hold note secrets, pass --tls-cert and --tls-key to listen on {}", args.listen);
+        return ExitCode::FAILURE;
+    }
+    let token = args.token.unwrap_or_else(|| {
+        let token = format!("{:032x}{:032x}", rand::random::<u128>(), rand::random::<u128>());
+        println!("Token: {token}");
+        token
+    });
+
+    let queue = Arc::new(Queue::new(args.max_queue, Duration::from_secs(args.job_ttl), args.prover));
+    for _ in 0..args.workers.max(1) {
+        let queue = queue.clone();
+        thread::spawn(move || queue.work());
+    }
+    let expiring = queue.clone();
+    thread::spawn(move || expiring.expire());
+
+    let (server, scheme) = match tls {
+        Some(tls) => (Server::https(args.listen, tls), "https"),
+        None => (Server::http(args.listen), "http"),
+    };
+    let server = match server {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Failed to listen on {}: {e}", args.listen);
+            return ExitCode::FAILURE;
+        }
+    };
+    println!("Listening on {scheme}://{} with {} workers", args.listen, args.workers.max(1));
+    for mut request in server.incoming_requests() {
+        let response = if authorized(&request, &token) {
+            handle(&queue, &mut request)
+        } else {
+            error(401, "Missing or wrong bearer token".into())
+        };
+        let _ = request.respond(response);
+    }
+    ExitCode::SUCCESS
+}
+
+/// Whether `request` carries `token`, compared in constant time.
+fn authorized(request: &Request, token: &str) -> bool {
+    let Some(header) = request.headers().iter().find(|header| header.field.equiv("Authorization")) else {
+        return false;
+    };
+    let Some(sent) = header.value.as_str().strip_prefix("Bearer ") else {
+        return false;
+    };
+    sent.len() == token.len() && sent.bytes().zip(token.bytes()).fold(0, |diff, (a, b)| diff | (a ^ b)) == 0
+}
+
+fn handle(queue: &Queue, request: &mut Request) -> HttpResponse {
+    let path: Vec<&str> = request.url().trim_matches('/').split('/').collect();
+    let method = request.method().clone();
+
+    match (method, path.as_slice()) {
+        (Method::Post, ["jobs"]) => {
+            let mut body = Vec::new();
+            if let Err(e) = request.as_reader().read_to_end(&mut body) {
+                return error(400, e.to_string());
+            }
+            let input: WithdrawInput = match serde_json::from_slice(&body) {
+                Ok(input) => input,
+                Err(e) => return error(400, format!("Invalid withdraw input: {e}")),
+            };
+            if let Err(e) = input.validate() {
+                return error(400, format!("Invalid withdraw input: {e}"));
+            }
+            match queue.submit(input) {
+                Ok(id) => json(202, &serde_json::json!({ "id": id })),
+                Err(e) => queue_error(e),
+            }
+        }
+        (Method::Get, ["jobs", id]) => match queue.status(id) {
+            Ok(status) => json(200, &status),
+            Err(e) => queue_error(e),
+        },
+        (Method::Get, ["jobs", id, "receipt"]) => match queue.receipt(id) {
+            Ok(receipt) => Response::from_data(receipt)
+                .with_header(Header::from_bytes("Content-Type", "application/octet-stream").unwrap()),
+            Err(e) => queue_error(e),
+        },
+        (Method::Delete, ["jobs", id]) => match queue.remove(id) {
+            Ok(()) => Response::from_data(Vec::new()).with_status_code(204),
+            Err(e) => queue_error(e),
+        },
+        _ => error(404, "Not found".into()),
+    }
+}
+
+fn json(status: u16, value: &impl Serialize) -> HttpResponse {
+    Response::from_data(serde_json::to_vec(value).unwrap())
+        .with_status_code(status)
+        .with_header(Header::from_bytes("Content-Type", "application/json").unwrap())
+}
+
+fn error(status: u16, error: String) -> HttpResponse {
+    json(status, &serde_json::json!({ "error": error }))
+}
+
+fn queue_error(e: QueueError) -> HttpResponse {
+    match e {
+        QueueError::Full => error(503, "Queue is full".into()),
+        QueueError::NotFound => error(404, "Unknown job".into()),
+        QueueError::Busy => error(409, "Job is not finished".into()),
+        QueueError::Failed(e) => error(409, format!("Job failed: {e}")),
+    }
+}
@@ -0,0 +1,223 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    error::Error,
+    sync::{Condvar, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use methods::{HELLO_GUEST_ELF, HELLO_GUEST_ID};
+use mixer_backend::ProverConfig;
+use mixer_protocol::WithdrawInput;
+use serde::Serialize;
+
+/// Job ids are random, so one client can't guess another's.
+pub type JobId = String;
+
+/// Longest expired jobs are kept past their TTL.
+const EXPIRY_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Serialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum State {
+    Queued,
+    /// Running the guest without proving, to fail fast and count cycles.
+    Executing,
+    Proving,
+    Done,
+    Failed,
+}
+
+struct Job {
+    state: State,
+    input: Option<WithdrawInput>,
+    cycles: Option<u64>,
+    segments: Option<usize>,
+    error: Option<String>,
+    receipt: Option<Vec<u8>>,
+    submitted: Instant,
+    finished: Option<Instant>,
+}
+
+#[derive(Serialize)]
+pub struct JobStatus {
+    pub id: JobId,
+    pub state: State,
+    /// Jobs ahead of this one, while it is queued.
+    pub position: Option<usize>,
+    pub cycles: Option<u64>,
+    pub segments: Option<usize>,
+    pub error: Option<String>,
+    pub elapsed_ms: u128,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum QueueError {
+    Full,
+    NotFound,
+    /// The job is being executed or proved and can't be removed or read yet.
+    Busy,
+    /// The job failed; holds its error.
+    Failed(String),
+}
+
+#[derive(Default)]
+struct Inner {
+    pending: VecDeque<JobId>,
+    jobs: HashMap<JobId, Job>,
+}
+
+/// Withdraw inputs waiting to be proved and the results of finished jobs,
+/// which are kept until the receipt is fetched, the client deletes them or
+/// they outlive `ttl`.
+pub struct Queue {
+    inner: Mutex<Inner>,
+    ready: Condvar,
+    capacity: usize,
+    ttl: Duration,
+    config: ProverConfig,
+}
+
+impl Queue {
+    pub fn new(capacity: usize, ttl: Duration, config: ProverConfig) -> Self {
+        Self {
+            inner: Mutex::default(),
+            ready: Condvar::new(),
+            capacity,
+            ttl,
+            config,
+        }
+    }
+
+    pub fn submit(&self, input: WithdrawInput) -> Result<JobId, QueueError> {
+        let mut inner = self.inner.lock().unwrap();
+        if inner.pending.len() >= self.capacity {
+            return Err(QueueError::Full);
+        }
+        let id = format!("{:032x}", rand::random::<u128>());
+        inner.jobs.insert(
+            id.clone(),
+            Job {
+                state: State::Queued,
+                input: Some(input),
+                cycles: None,
+                segments: None,
+                error: None,
+                receipt: None,
+                submitted: Instant::now(),
+                finished: None,
+            },
+        );
+        inner.pending.push_back(id.clone());
+        self.ready.notify_one();
+        Ok(id)
+    }
+
+    pub fn status(&self, id: &str) -> Result<JobStatus, QueueError> {
+        let inner = self.inner.lock().unwrap();
+        inner.status(id).ok_or(QueueError::NotFound)
+    }
+
+    /// The postcard-encoded `(image id, receipt)` pair of a finished job. The
+    /// job is dropped with it, so the receipt is handed out only once.
+    pub fn receipt(&self, id: &str) -> Result<Vec<u8>, QueueError> {
+        let mut inner = self.inner.lock().unwrap();
+        let job = inner.jobs.get_mut(id).ok_or(QueueError::NotFound)?;
+        if let Some(e) = &job.error {
+            return Err(QueueError::Failed(e.clone()));
+        }
+        let receipt = job.receipt.take().ok_or(QueueError::Busy)?;
+        inner.jobs.remove(id);
+        Ok(receipt)
+    }
+
+    /// Drops a queued or finished job.
+    pub fn remove(&self, id: &str) -> Result<(), QueueError> {
+        let mut inner = self.inner.lock().unwrap();
+        match inner.jobs.get(id).map(|job| job.state) {
+            None => Err(QueueError::NotFound),
+            Some(State::Executing | State::Proving) => Err(QueueError::Busy),
+            Some(_) => {
+                inner.jobs.remove(id);
+                inner.pending.retain(|pending| pending != id);
+                Ok(())
+            }
+        }
+    }
+
+    /// Drops jobs that finished more than `ttl` ago, once in a while, forever.
+    pub fn expire(&self) {
+        loop {
+            thread::sleep(self.ttl.min(EXPIRY_INTERVAL));
+            let mut inner = self.inner.lock().unwrap();
+            inner.jobs.retain(|_, job| job.finished.is_none_or(|finished| finished.elapsed() < self.ttl));
+        }
+    }
+
+    /// Proves queued jobs one at a time, forever.
+    pub fn work(&self) {
+        loop {
+            let (id, input) = self.next();
+            let result = self.prove(&id, &input).map_err(|e| e.to_string());
+
+            let mut inner = self.inner.lock().unwrap();
+            // The job can't be removed while it runs.
+            let job = inner.jobs.get_mut(&id).unwrap();
+            job.finished = Some(Instant::now());
+            match result {
+                Ok(receipt) => {
+                    job.state = State::Done;
+                    job.receipt = Some(receipt);
+                }
+                Err(e) => {
+                    job.state = State::Failed;
+                    job.error = Some(e);
+                }
+            }
+        }
+    }
+
+    fn next(&self) -> (JobId, WithdrawInput) {
+        let mut inner = self.inner.lock().unwrap();
+        loop {
+            if let Some(id) = inner.pending.pop_front() {
+                let job = inner.jobs.get_mut(&id).unwrap();
+                job.state = State::Executing;
+                return (id, job.input.take().unwrap());
+            }
+            inner = self.ready.wait(inner).unwrap();
+        }
+    }
+
+    fn prove(&self, id: &str, input: &WithdrawInput) -> Result<Vec<u8>, Box<dyn Error>> {
+        let env = self.config.env().write(input)?.build()?;
+        let session = self.config.executor().execute(env, HELLO_GUEST_ELF)?;
+        {
+            let mut inner = self.inner.lock().unwrap();
+            let job = inner.jobs.get_mut(id).unwrap();
+            job.state = State::Proving;
+            job.cycles = Some(session.cycles());
+            job.segments = Some(session.segments.len());
+        }
+
+        let env = self.config.env().write(input)?.build()?;
+        let receipt = self.config.prove(env, HELLO_GUEST_ELF)?;
+        Ok(postcard::to_allocvec(&(HELLO_GUEST_ID, receipt))?)
+    }
+}
+
+impl Inner {
+    fn status(&self, id: &str) -> Option<JobStatus> {
+        let job = self.jobs.get(id)?;
+        let elapsed = job.finished.unwrap_or_else(Instant::now) - job.submitted;
+        Some(JobStatus {
+            id: id.to_owned(),
+            state: job.state,
+            position: self.pending.iter().position(|pending| pending == id),
+            cycles: job.cycles,
+            segments: job.segments,
+            error: job.error.clone(),
+            elapsed_ms: elapsed.as_millis(),
+        })
+    }
+}